@@ -78,12 +78,49 @@ impl Algorithm {
         }
     }
 
+    /// Does this [`Algorithm`] resist side-channel attacks?
+    ///
+    /// Returns `false` for [`Algorithm::Argon2d`], whose password-dependent memory access
+    /// pattern can leak information about the password through cache timing, e.g. to other
+    /// tenants of a shared host.
+    #[must_use]
+    pub const fn is_side_channel_resistant(&self) -> bool {
+        !matches!(self, Algorithm::Argon2d)
+    }
+
     /// Serialize primitive type as little endian bytes
     pub(crate) const fn to_le_bytes(self) -> [u8; 4] {
         (self as u32).to_le_bytes()
     }
 }
 
+/// Policy restricting which [`Algorithm`]s an [`Argon2`][`crate::Argon2`] context will compute
+/// when the algorithm is selected by its input, e.g. when verifying a PHC string hash.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum AlgorithmPolicy {
+    /// Allow all algorithms (*default*).
+    #[default]
+    AllowAll,
+
+    /// Only allow algorithms which are side-channel resistant, i.e. reject
+    /// [`Algorithm::Argon2d`].
+    ///
+    /// Recommended when verifying hashes from untrusted input on shared hosts.
+    SideChannelResistant,
+}
+
+impl AlgorithmPolicy {
+    /// Does this policy permit the given [`Algorithm`]?
+    #[must_use]
+    pub const fn permits(&self, algorithm: Algorithm) -> bool {
+        match self {
+            AlgorithmPolicy::AllowAll => true,
+            AlgorithmPolicy::SideChannelResistant => algorithm.is_side_channel_resistant(),
+        }
+    }
+}
+
 impl AsRef<str> for Algorithm {
     fn as_ref(&self) -> &str {
         self.as_str()
@@ -0,0 +1,230 @@
+//! Implementations of the Argon2 compression function.
+
+use crate::Block;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+cpufeatures::new!(avx2_cpuid, "avx2");
+
+/// Implementation ("backend") of the Argon2 compression function.
+///
+/// By default the fastest backend available on the current CPU is used.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Portable implementation.
+    Scalar,
+
+    /// Implementation optimized for AVX2, detected at runtime on `x86`/`x86_64` CPUs.
+    Avx2,
+
+    /// WebAssembly SIMD128 implementation, enabled at compile time on `wasm32` targets with
+    /// `RUSTFLAGS="-C target-feature=+simd128"`.
+    Simd128,
+}
+
+impl Backend {
+    /// All backends, whether available or not.
+    pub const ALL: [Backend; 3] = [Backend::Scalar, Backend::Avx2, Backend::Simd128];
+
+    /// Detect the fastest backend available on the current CPU.
+    #[must_use]
+    pub fn detect() -> Self {
+        if Backend::Simd128.is_available() {
+            Backend::Simd128
+        } else if Backend::Avx2.is_available() {
+            Backend::Avx2
+        } else {
+            Backend::Scalar
+        }
+    }
+
+    /// Is this backend available on the current CPU?
+    #[must_use]
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => avx2_cpuid::get(),
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Get the name of this backend.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Backend::Scalar => "scalar",
+            Backend::Avx2 => "avx2",
+            Backend::Simd128 => "simd128",
+        }
+    }
+
+    /// Resolve this backend for computing the compression function, falling back to
+    /// [`Backend::Scalar`] if it isn't available.
+    ///
+    /// This performs CPU feature detection, so it should be called once per hash rather than
+    /// once per block.
+    pub(crate) fn resolve(self) -> ResolvedBackend {
+        if self.is_available() {
+            ResolvedBackend(self)
+        } else {
+            ResolvedBackend(Backend::Scalar)
+        }
+    }
+}
+
+/// [`Backend`] which is known to be available on the current CPU.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ResolvedBackend(Backend);
+
+impl ResolvedBackend {
+    /// Compute the compression function using this backend.
+    #[inline(always)]
+    pub(crate) fn compress(self, rhs: &Block, lhs: &Block) -> Block {
+        match self.0 {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => {
+                /// Enable AVX2 optimizations.
+                #[target_feature(enable = "avx2")]
+                unsafe fn compress_avx2(rhs: &Block, lhs: &Block) -> Block {
+                    Block::compress(rhs, lhs)
+                }
+
+                // SAFETY: `Backend::resolve` checked that AVX2 was detected.
+                unsafe { compress_avx2(rhs, lhs) }
+            }
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => crate::block::simd128::compress(rhs, lhs),
+            _ => Block::compress(rhs, lhs),
+        }
+    }
+}
+
+/// Compression function computed for `N` independent pairs of blocks at once.
+pub(crate) trait CompressMany<const N: usize> {
+    /// Compute the compression function of each pair of blocks `rhs[i]` and `lhs[i]`.
+    fn compress_many(self, rhs: [&Block; N], lhs: [&Block; N]) -> [Block; N];
+}
+
+impl CompressMany<1> for ResolvedBackend {
+    #[inline(always)]
+    fn compress_many(self, [rhs]: [&Block; 1], [lhs]: [&Block; 1]) -> [Block; 1] {
+        [self.compress(rhs, lhs)]
+    }
+}
+
+impl CompressMany<2> for ResolvedBackend {
+    /// Interleave the computation of both compressions.
+    #[inline(always)]
+    fn compress_many(self, rhs: [&Block; 2], lhs: [&Block; 2]) -> [Block; 2] {
+        match self.0 {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => {
+                /// Enable AVX2 optimizations.
+                #[target_feature(enable = "avx2")]
+                unsafe fn compress_x2_avx2(rhs: [&Block; 2], lhs: [&Block; 2]) -> [Block; 2] {
+                    Block::compress_x2(rhs, lhs)
+                }
+
+                // SAFETY: `Backend::resolve` checked that AVX2 was detected.
+                unsafe { compress_x2_avx2(rhs, lhs) }
+            }
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => [
+                crate::block::simd128::compress(rhs[0], lhs[0]),
+                crate::block::simd128::compress(rhs[1], lhs[1]),
+            ],
+            _ => Block::compress_x2(rhs, lhs),
+        }
+    }
+}
+
+impl core::fmt::Display for Backend {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, CompressMany};
+    use crate::{Argon2, Block, Error};
+
+    #[test]
+    fn compress_against_scalar() {
+        for backend in Backend::ALL.into_iter().filter(|b| b.is_available()) {
+            let mut rhs = Block::new();
+            let mut lhs = Block::new();
+
+            for (i, (r, l)) in rhs.as_mut().iter_mut().zip(lhs.as_mut()).enumerate() {
+                *r = (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                *l = !(i as u64).rotate_left(32);
+            }
+
+            for _round in 0..10 {
+                let expected = Block::compress(&rhs, &lhs);
+                let output = backend.resolve().compress(&rhs, &lhs);
+
+                assert_eq!(
+                    output.as_ref(),
+                    expected.as_ref(),
+                    "output != expected, {backend} backend is not correct?"
+                );
+
+                lhs = rhs;
+                rhs = output;
+            }
+        }
+    }
+
+    #[test]
+    fn compress_many_against_scalar() {
+        for backend in Backend::ALL.into_iter().filter(|b| b.is_available()) {
+            let mut rhs = [Block::new(); 2];
+            let mut lhs = [Block::new(); 2];
+
+            for (k, (rhs, lhs)) in rhs.iter_mut().zip(&mut lhs).enumerate() {
+                for (i, (r, l)) in rhs.as_mut().iter_mut().zip(lhs.as_mut()).enumerate() {
+                    *r = ((i + k) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                    *l = !((i * (k + 1)) as u64).rotate_left(32);
+                }
+            }
+
+            for _round in 0..10 {
+                let expected = [0, 1].map(|k| Block::compress(&rhs[k], &lhs[k]));
+                let output = backend
+                    .resolve()
+                    .compress_many([&rhs[0], &rhs[1]], [&lhs[0], &lhs[1]]);
+
+                for k in 0..2 {
+                    assert_eq!(
+                        output[k].as_ref(),
+                        expected[k].as_ref(),
+                        "output != expected, {backend} backend is not correct?"
+                    );
+                }
+
+                lhs = rhs;
+                rhs = output;
+            }
+        }
+    }
+
+    #[test]
+    fn force_backend() {
+        assert_eq!(Argon2::default().backend(), Backend::detect());
+
+        for backend in Backend::ALL {
+            match Argon2::default().with_backend(backend) {
+                Ok(ctx) => assert_eq!(ctx.backend(), backend),
+                Err(err) => {
+                    assert!(!backend.is_available());
+                    assert_eq!(err, Error::BackendUnavailable);
+                }
+            }
+        }
+    }
+}
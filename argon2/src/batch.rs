@@ -0,0 +1,217 @@
+//! Options for hashing many passwords at once.
+
+use crate::Block;
+
+/// Controls how many threads [`Argon2::hash_many`][crate::Argon2::hash_many] uses, and how much
+/// memory they may use.
+///
+/// Each hash being computed needs `m_cost` KiB of memory. Hashes are evenly split between at most
+/// [`BatchOptions::threads`] threads, each of which allocates memory once and reuses it for all
+/// of its hashes. The number of threads is reduced so that the total memory stays within
+/// [`BatchOptions::max_memory`], down to computing all hashes on the calling thread.
+///
+/// Threads are spawned using [rayon](https://docs.rs/rayon) if the `parallel` feature is enabled,
+/// and never more than the size of its thread pool. Otherwise all hashes are computed on the
+/// calling thread.
+///
+/// Each thread can compute two hashes at a time, interleaving their compressions, which needs
+/// twice the memory per thread. This isn't faster on every CPU: the compression function of a
+/// single hash already has plenty of instruction-level parallelism, and the memory of two hashes
+/// puts more pressure on caches. It must therefore be enabled with
+/// [`BatchOptions::with_interleave`], after benchmarking it on the target hardware.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BatchOptions {
+    /// Maximum number of threads
+    threads: usize,
+
+    /// Maximum memory in bytes
+    max_memory: usize,
+
+    /// Whether threads may compute two hashes at a time
+    interleave: bool,
+}
+
+impl BatchOptions {
+    /// Compute all hashes on the calling thread.
+    pub const SEQUENTIAL: Self = Self::new(1);
+
+    /// Use one thread per hash, up to the size of the thread pool, without a memory limit.
+    ///
+    /// This is the default when the `parallel` feature is enabled.
+    pub const UNBOUNDED: Self = Self::new(usize::MAX);
+
+    /// Use up to `threads` threads, without a memory limit.
+    ///
+    /// A `threads` value of `0` is treated as `1`.
+    #[must_use]
+    pub const fn new(threads: usize) -> Self {
+        Self {
+            threads: if threads == 0 { 1 } else { threads },
+            max_memory: usize::MAX,
+            interleave: false,
+        }
+    }
+
+    /// Limit the total memory to `max_memory` bytes, reducing the number of threads used when it
+    /// would be exceeded.
+    ///
+    /// The limit only bounds parallelism: hashes whose memory exceeds it are still computed one
+    /// at a time.
+    #[must_use]
+    pub const fn with_max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// Allow each thread to compute two hashes at a time, doubling the memory used by each
+    /// thread.
+    ///
+    /// Hashes are only interleaved if the doubled memory stays within
+    /// [`BatchOptions::max_memory`].
+    #[must_use]
+    pub const fn with_interleave(mut self, interleave: bool) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+    /// Maximum number of threads.
+    #[must_use]
+    pub const fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Maximum memory in bytes.
+    #[must_use]
+    pub const fn max_memory(&self) -> usize {
+        self.max_memory
+    }
+
+    /// Whether each thread may compute two hashes at a time.
+    #[must_use]
+    pub const fn interleave(&self) -> bool {
+        self.interleave
+    }
+
+    /// Plan how to compute `hashes` hashes of `block_count` blocks each: use at most one thread
+    /// per hash, and as many threads as fit within the memory limit. If enabled and memory allows,
+    /// each thread computes two hashes at a time.
+    pub(crate) fn plan(&self, hashes: usize, block_count: usize) -> BatchPlan {
+        let hash_memory = block_count.saturating_mul(Block::SIZE).max(1);
+        let memories = self.max_memory / hash_memory;
+
+        #[cfg(feature = "parallel")]
+        let pool_threads = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let pool_threads = 1;
+
+        let threads = self
+            .threads
+            .min(pool_threads)
+            .min(hashes)
+            .min(memories)
+            .max(1);
+
+        let interleave = self.interleave
+            && hashes.div_ceil(threads) >= 2
+            && threads.saturating_mul(2) <= memories;
+
+        BatchPlan {
+            threads,
+            interleave,
+        }
+    }
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        if cfg!(feature = "parallel") {
+            Self::UNBOUNDED
+        } else {
+            Self::SEQUENTIAL
+        }
+    }
+}
+
+/// How to compute a batch of hashes, as planned by [`BatchOptions::plan`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct BatchPlan {
+    /// Number of threads, each allocating its own memory
+    pub(crate) threads: usize,
+
+    /// Whether each thread computes two hashes at a time
+    pub(crate) interleave: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchOptions, BatchPlan};
+    use crate::Block;
+
+    #[test]
+    fn plan() {
+        let block_count = 32;
+        let hash_memory = block_count * Block::SIZE;
+
+        assert_eq!(
+            BatchOptions::SEQUENTIAL.plan(10, block_count),
+            BatchPlan {
+                threads: 1,
+                interleave: false
+            }
+        );
+
+        // Interleaving is opt-in, and needs memory for two hashes
+        assert!(
+            BatchOptions::SEQUENTIAL
+                .with_interleave(true)
+                .plan(10, block_count)
+                .interleave
+        );
+        assert!(
+            !BatchOptions::SEQUENTIAL
+                .with_interleave(true)
+                .with_max_memory(2 * hash_memory - 1)
+                .plan(10, block_count)
+                .interleave
+        );
+        assert!(
+            !BatchOptions::SEQUENTIAL
+                .with_interleave(true)
+                .plan(1, block_count)
+                .interleave
+        );
+
+        // The memory limit bounds the number of threads, down to one
+        for max_memory in [0, hash_memory - 1, hash_memory, 2 * hash_memory - 1] {
+            assert_eq!(
+                BatchOptions::UNBOUNDED
+                    .with_max_memory(max_memory)
+                    .plan(10, block_count)
+                    .threads,
+                1
+            );
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            let pool_threads = rayon::current_num_threads();
+            assert_eq!(
+                BatchOptions::UNBOUNDED
+                    .plan(usize::MAX, block_count)
+                    .threads,
+                pool_threads
+            );
+            assert_eq!(
+                BatchOptions::UNBOUNDED
+                    .with_max_memory(2 * hash_memory)
+                    .plan(10, block_count)
+                    .threads,
+                pool_threads.min(2)
+            );
+            assert_eq!(BatchOptions::UNBOUNDED.plan(1, block_count).threads, 1);
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        assert_eq!(BatchOptions::UNBOUNDED.plan(10, block_count).threads, 1);
+    }
+}
@@ -10,6 +10,13 @@ use core::{
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
+#[cfg(any(feature = "alloc", feature = "zeroize"))]
+mod mlock;
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+mod mmap;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(crate) mod simd128;
+
 const TRUNC: u64 = u32::MAX as u64;
 
 #[rustfmt::skip]
@@ -74,8 +81,10 @@ impl Block {
         self.0.iter()
     }
 
+    /// Portable implementation of the compression function.
+    ///
     /// NOTE: do not call this directly. It should only be called via
-    /// `Argon2::compress`.
+    /// `ResolvedBackend::compress`.
     #[inline(always)]
     pub(crate) fn compress(rhs: &Self, lhs: &Self) -> Self {
         let r = *rhs ^ lhs;
@@ -112,6 +121,59 @@ impl Block {
         q ^= &r;
         q
     }
+
+    /// Portable implementation of the compression function for two independent pairs of blocks,
+    /// interleaving the permutations of both.
+    ///
+    /// NOTE: do not call this directly. It should only be called via
+    /// `ResolvedBackend::compress_many`.
+    #[inline(always)]
+    pub(crate) fn compress_x2(rhs: [&Self; 2], lhs: [&Self; 2]) -> [Self; 2] {
+        let r = [*rhs[0] ^ lhs[0], *rhs[1] ^ lhs[1]];
+
+        // Apply permutations rowwise
+        let [mut q0, mut q1] = r;
+        for (a, b) in q0.0.chunks_exact_mut(16).zip(q1.0.chunks_exact_mut(16)) {
+            #[rustfmt::skip]
+            permute!(
+                a[0], a[1], a[2], a[3],
+                a[4], a[5], a[6], a[7],
+                a[8], a[9], a[10], a[11],
+                a[12], a[13], a[14], a[15],
+            );
+
+            #[rustfmt::skip]
+            permute!(
+                b[0], b[1], b[2], b[3],
+                b[4], b[5], b[6], b[7],
+                b[8], b[9], b[10], b[11],
+                b[12], b[13], b[14], b[15],
+            );
+        }
+
+        // Apply permutations columnwise
+        for i in 0..8 {
+            let b = i * 2;
+
+            for q in [&mut q0, &mut q1] {
+                #[rustfmt::skip]
+                permute!(
+                    q.0[b], q.0[b + 1],
+                    q.0[b + 16], q.0[b + 17],
+                    q.0[b + 32], q.0[b + 33],
+                    q.0[b + 48], q.0[b + 49],
+                    q.0[b + 64], q.0[b + 65],
+                    q.0[b + 80], q.0[b + 81],
+                    q.0[b + 96], q.0[b + 97],
+                    q.0[b + 112], q.0[b + 113],
+                );
+            }
+        }
+
+        q0 ^= &r[0];
+        q1 ^= &r[1];
+        [q0, q1]
+    }
 }
 
 impl Default for Block {
@@ -157,10 +219,18 @@ impl Zeroize for Block {
 }
 
 /// Custom implementation of `Box<[Block]>` until `Box::try_new_zeroed_slice` is stabilized.
+///
+/// With the `hugepages` feature enabled on Linux, large allocations are served by `mmap` instead
+/// of the global allocator.
+///
+/// The blocks are locked into RAM when the `mlock` feature is enabled, and are zeroized on drop
+/// when the `zeroize` feature is enabled.
 #[cfg(feature = "alloc")]
 pub(crate) struct Blocks {
     p: core::ptr::NonNull<Block>,
     len: usize,
+    #[cfg(all(feature = "hugepages", target_os = "linux"))]
+    mmap: bool,
 }
 
 #[cfg(feature = "alloc")]
@@ -173,12 +243,25 @@ impl Blocks {
             return None;
         }
 
+        #[cfg(all(feature = "hugepages", target_os = "linux"))]
+        if let Some(p) = mmap::alloc(len) {
+            return Some(Self { p, len, mmap: true });
+        }
+
         let layout = Layout::array::<Block>(len).ok()?;
         // SAFETY: `alloc_zeroed` is used correctly with non-zero layout
         let p = unsafe { alloc_zeroed(layout) };
 
         let p = NonNull::new(p.cast())?;
-        Some(Self { p, len })
+        let mut blocks = Self {
+            p,
+            len,
+            #[cfg(all(feature = "hugepages", target_os = "linux"))]
+            mmap: false,
+        };
+
+        mlock::lock(blocks.as_slice());
+        Some(blocks)
     }
 
     pub fn as_slice(&mut self) -> &mut [Block] {
@@ -191,6 +274,19 @@ impl Blocks {
 impl Drop for Blocks {
     fn drop(&mut self) {
         use alloc::alloc::{Layout, dealloc};
+
+        #[cfg(feature = "zeroize")]
+        self.as_slice().iter_mut().for_each(Zeroize::zeroize);
+
+        #[cfg(all(feature = "hugepages", target_os = "linux"))]
+        if self.mmap {
+            // SAFETY: the pointer was returned by `mmap::alloc` for `self.len` blocks
+            unsafe { mmap::dealloc(self.p, self.len) };
+            return;
+        }
+
+        mlock::unlock(self.as_slice());
+
         // SAFETY: layout was checked during construction
         let layout = unsafe { Layout::array::<Block>(self.len).unwrap_unchecked() };
         // SAFETY: we use `dealloc` correctly with the previously allocated pointer
@@ -199,3 +295,54 @@ impl Drop for Blocks {
         }
     }
 }
+
+/// Wrapper for caller-provided Argon2 memory blocks which zeroizes them on drop.
+///
+/// This gives memory passed to [`Argon2::hash_password_into_with_memory`] or
+/// [`Argon2::fill_memory`] the same treatment as memory allocated internally by
+/// [`Argon2::hash_password_into`]:
+///
+/// - the blocks are zeroized when the wrapper is dropped
+/// - with the `mlock` feature enabled on Unix targets, the blocks are locked into RAM for the
+///   lifetime of the wrapper so they can't be written out to swap
+///
+/// [`Argon2::hash_password_into_with_memory`]: crate::Argon2::hash_password_into_with_memory
+/// [`Argon2::fill_memory`]: crate::Argon2::fill_memory
+/// [`Argon2::hash_password_into`]: crate::Argon2::hash_password_into
+#[cfg(feature = "zeroize")]
+pub struct ZeroizingBlocks<'a> {
+    blocks: &'a mut [Block],
+}
+
+#[cfg(feature = "zeroize")]
+impl<'a> ZeroizingBlocks<'a> {
+    /// Wrap the given memory blocks, locking them into RAM if the `mlock` feature is enabled.
+    pub fn new(blocks: &'a mut [Block]) -> Self {
+        mlock::lock(blocks);
+        Self { blocks }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl AsMut<[Block]> for ZeroizingBlocks<'_> {
+    fn as_mut(&mut self) -> &mut [Block] {
+        self.blocks
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl core::fmt::Debug for ZeroizingBlocks<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ZeroizingBlocks")
+            .field("len", &self.blocks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ZeroizingBlocks<'_> {
+    fn drop(&mut self) {
+        self.blocks.iter_mut().for_each(Zeroize::zeroize);
+        mlock::unlock(self.blocks);
+    }
+}
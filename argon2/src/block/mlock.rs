@@ -0,0 +1,57 @@
+//! Locking of Argon2 memory blocks into RAM.
+//!
+//! With the `mlock` feature enabled on Unix targets, memory holding password-derived blocks is
+//! locked with `mlock(2)` so it can't be written out to swap. Otherwise these functions are no-ops.
+//!
+//! Locking is best-effort: it fails if `RLIMIT_MEMLOCK` is too small for the requested `m_cost`,
+//! in which case the memory simply remains swappable.
+//!
+//! Locks apply to whole pages and don't nest, so unlocking a page shared with another allocation
+//! would also unlock that allocation. Only the pages lying entirely within the blocks are locked
+//! and unlocked: the partial pages at either end, if any, remain swappable.
+
+use super::Block;
+
+/// Lock the given blocks into RAM.
+#[cfg(all(feature = "mlock", unix))]
+pub(crate) fn lock(blocks: &[Block]) {
+    if let Some((p, len)) = whole_pages(blocks) {
+        // SAFETY: the pointer and length describe whole pages within memory borrowed from
+        // `blocks`. `mlock` doesn't access the memory, and failure is tolerated as described
+        // above.
+        unsafe { libc::mlock(p, len) };
+    }
+}
+
+/// Lock the given blocks into RAM.
+#[cfg(not(all(feature = "mlock", unix)))]
+pub(crate) fn lock(_blocks: &[Block]) {}
+
+/// Unlock blocks previously locked with [`lock`].
+#[cfg(all(feature = "mlock", unix))]
+pub(crate) fn unlock(blocks: &[Block]) {
+    if let Some((p, len)) = whole_pages(blocks) {
+        // SAFETY: the pointer and length describe whole pages within memory borrowed from
+        // `blocks`. Unlocking memory which isn't locked is a no-op.
+        unsafe { libc::munlock(p, len) };
+    }
+}
+
+/// Unlock blocks previously locked with [`lock`].
+#[cfg(not(all(feature = "mlock", unix)))]
+pub(crate) fn unlock(_blocks: &[Block]) {}
+
+/// Start and length of the range of whole pages within `blocks`, if any.
+#[cfg(all(feature = "mlock", unix))]
+fn whole_pages(blocks: &[Block]) -> Option<(*const libc::c_void, usize)> {
+    // SAFETY: `sysconf` has no preconditions
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let page_size = usize::try_from(page_size).ok().filter(|&size| size > 0)?;
+
+    let p = blocks.as_ptr().cast::<u8>();
+    let offset = p.align_offset(page_size);
+    let len = size_of_val(blocks).checked_sub(offset)?;
+    let len = len - len % page_size;
+
+    (len > 0).then(|| (p.wrapping_add(offset).cast(), len))
+}
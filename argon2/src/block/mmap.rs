@@ -0,0 +1,216 @@
+//! `mmap`-backed storage for Argon2 memory blocks (Linux only).
+//!
+//! With `m_cost` in the GiB range, memory obtained from the global allocator is backed by 4 KiB
+//! pages which are faulted in one at a time, and the working set thrashes the TLB. Mapping the
+//! blocks using huge pages avoids both, and locking the mapping keeps password-derived data from
+//! being written out to swap.
+
+use super::Block;
+use core::{ffi::c_void, ptr::NonNull};
+
+/// Size of the huge pages requested from `mmap`.
+///
+/// The system default huge page size varies (e.g. 1 GiB with `default_hugepagesz=1G`, or 512 MiB
+/// on aarch64 with 64 KiB base pages), so mappings explicitly request this size using
+/// `MAP_HUGE_2MB` for lengths to be rounded to the right multiple.
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// `mmap` flags requesting huge pages of [`HUGE_PAGE_SIZE`] bytes.
+const HUGE_PAGE_FLAGS: libc::c_int = libc::MAP_HUGETLB | libc::MAP_HUGE_2MB;
+
+/// Try to allocate zeroed memory for `len` blocks using `mmap`.
+///
+/// Explicit 2 MiB huge pages (`MAP_HUGETLB`) are tried first, which requires the system
+/// administrator to have reserved a pool of huge pages of that size. If that fails, a regular anonymous mapping is created and
+/// hinted for transparent huge pages (`MADV_HUGEPAGE`).
+///
+/// Locking the mapping with `mlock` is best-effort: it fails if `RLIMIT_MEMLOCK` is too small,
+/// in which case the memory remains swappable.
+///
+/// Returns `None` if the allocation is too small to benefit from huge pages or if the mapping
+/// couldn't be created, in which case the caller should fall back to the global allocator.
+pub(super) fn alloc(len: usize) -> Option<NonNull<Block>> {
+    let size = len.checked_mul(Block::SIZE)?;
+
+    if size < HUGE_PAGE_SIZE {
+        return None;
+    }
+
+    let size = map_len(len)?;
+
+    let p = map(size, HUGE_PAGE_FLAGS).or_else(|| {
+        let p = map(size, 0)?;
+
+        // SAFETY: `p` is a live mapping of `size` bytes. Failure only means the kernel was built
+        // without transparent huge page support, in which case regular pages are used.
+        unsafe { libc::madvise(p.as_ptr(), size, libc::MADV_HUGEPAGE) };
+
+        Some(p)
+    })?;
+
+    // SAFETY: `p` is a live mapping of `size` bytes. Failure is tolerated as described above.
+    unsafe { libc::mlock(p.as_ptr(), size) };
+
+    Some(p.cast())
+}
+
+/// Unmap memory previously allocated using [`alloc`].
+///
+/// # Safety
+///
+/// `p` must have been returned by [`alloc`] called with the same `len`, and must not be used
+/// afterwards.
+pub(super) unsafe fn dealloc(p: NonNull<Block>, len: usize) {
+    // SAFETY: forwarded from the function's safety contract.
+    let unmapped = unsafe { unmap(p, len) };
+    debug_assert!(unmapped, "munmap failed");
+}
+
+/// Unlock and unmap memory previously allocated using [`alloc`], returning whether `munmap`
+/// succeeded.
+///
+/// # Safety
+///
+/// See [`dealloc`].
+unsafe fn unmap(p: NonNull<Block>, len: usize) -> bool {
+    // Lengths are checked in `alloc`
+    let Some(size) = map_len(len) else {
+        return false;
+    };
+
+    // SAFETY: per the function's safety contract `p` is a live mapping of `size` bytes which is
+    // not used afterwards. Unlocking a region which isn't locked is a no-op.
+    unsafe {
+        libc::munlock(p.as_ptr().cast(), size);
+        libc::munmap(p.as_ptr().cast(), size) == 0
+    }
+}
+
+/// Size of the mapping for `len` blocks, rounded up to a multiple of the huge page size as
+/// required by `MAP_HUGETLB`.
+fn map_len(len: usize) -> Option<usize> {
+    len.checked_mul(Block::SIZE)?
+        .checked_next_multiple_of(HUGE_PAGE_SIZE)
+}
+
+/// Create a private anonymous mapping of `size` bytes with the given extra `flags`.
+fn map(size: usize, flags: libc::c_int) -> Option<NonNull<c_void>> {
+    // SAFETY: we request a new private anonymous mapping without an address hint, so no existing
+    // memory is affected. Anonymous mappings are zero-initialized by the kernel.
+    let p = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags,
+            -1,
+            0,
+        )
+    };
+
+    if p == libc::MAP_FAILED {
+        return None;
+    }
+
+    NonNull::new(p)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{HUGE_PAGE_FLAGS, HUGE_PAGE_SIZE, alloc, dealloc, map, map_len, unmap};
+    use crate::{Algorithm, Argon2, Block, Params, Version, block::Blocks};
+    use alloc::vec;
+
+    /// Number of blocks in one huge page, the smallest allocation served by `mmap`.
+    const HUGE_PAGE_BLOCKS: usize = HUGE_PAGE_SIZE / Block::SIZE;
+
+    #[test]
+    fn small_allocations_use_global_allocator() {
+        assert!(alloc(HUGE_PAGE_BLOCKS - 1).is_none());
+        assert!(!Blocks::new(HUGE_PAGE_BLOCKS - 1).unwrap().mmap);
+    }
+
+    #[test]
+    fn alloc_zeroed_and_dealloc() {
+        let len = HUGE_PAGE_BLOCKS + 1;
+        let p = alloc(len).unwrap();
+
+        // SAFETY: `p` points to `len` zero-initialized blocks
+        let blocks = unsafe { core::slice::from_raw_parts_mut(p.as_ptr(), len) };
+        assert!(blocks.iter().all(|b| b.as_ref().iter().all(|&w| w == 0)));
+        blocks[len - 1] = Block::new();
+
+        // SAFETY: `p` was returned by `alloc` for `len` blocks and isn't used afterwards
+        unsafe { dealloc(p, len) };
+    }
+
+    /// Regular mapping used when no huge page pool is reserved for `MAP_HUGETLB`.
+    #[test]
+    fn fallback_mapping() {
+        let len = HUGE_PAGE_BLOCKS;
+        let p = map(map_len(len).unwrap(), 0).unwrap();
+
+        // SAFETY: `p` is a mapping of `map_len(len)` bytes as created by `alloc`, and isn't used
+        // afterwards
+        assert!(unsafe { unmap(p.cast(), len) });
+    }
+
+    /// Mappings are unmapped whole, whichever kind of pages `alloc` obtained: with huge pages of
+    /// a size other than `HUGE_PAGE_SIZE`, `munmap` would fail with `EINVAL` and leak the mapping.
+    #[test]
+    fn unmap_succeeds() {
+        for len in [
+            HUGE_PAGE_BLOCKS,
+            HUGE_PAGE_BLOCKS + 1,
+            3 * HUGE_PAGE_BLOCKS - 1,
+        ] {
+            let p = alloc(len).unwrap();
+
+            // SAFETY: `p` was returned by `alloc` for `len` blocks and isn't used afterwards
+            assert!(unsafe { unmap(p, len) });
+        }
+
+        // Explicit huge pages are only available if a pool has been reserved
+        if let Some(p) = map(map_len(HUGE_PAGE_BLOCKS + 1).unwrap(), HUGE_PAGE_FLAGS) {
+            // SAFETY: `p` is a mapping of `map_len(len)` bytes as created by `alloc`, and isn't
+            // used afterwards
+            assert!(unsafe { unmap(p.cast(), HUGE_PAGE_BLOCKS + 1) });
+        }
+    }
+
+    /// Hashing with memory served by `mmap` gives the same output as with memory from the global
+    /// allocator.
+    #[test]
+    fn hash_matches_global_allocator() {
+        let params = Params::new(2048, 1, 2, Some(32)).unwrap();
+        let block_count = params.block_count();
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut blocks = Blocks::new(block_count).unwrap();
+        assert!(blocks.mmap);
+
+        let mut expected = [0u8; 32];
+        let mut memory = vec![Block::new(); block_count];
+        argon2
+            .hash_password_into_with_memory(b"password", b"somesalt", &mut expected, &mut memory)
+            .unwrap();
+
+        let mut actual = [0u8; 32];
+        argon2
+            .hash_password_into_with_memory(
+                b"password",
+                b"somesalt",
+                &mut actual,
+                blocks.as_slice(),
+            )
+            .unwrap();
+        assert_eq!(actual, expected);
+
+        let mut actual = [0u8; 32];
+        argon2
+            .hash_password_into(b"password", b"somesalt", &mut actual)
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+}
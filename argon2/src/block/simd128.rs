@@ -0,0 +1,123 @@
+//! WebAssembly SIMD128 implementation of the Argon2 compression function.
+//!
+//! Each row (and column) of 16 words is held in eight `v128` registers of two lanes each, so the
+//! BlaMka G function is computed for two columns (and then two diagonals) at a time.
+
+use super::Block;
+use core::arch::wasm32::*;
+
+/// Number of `v128` registers in a block.
+const LEN: usize = Block::SIZE / 16;
+
+/// SIMD128 version of [`Block::compress`].
+#[inline(always)]
+pub(crate) fn compress(rhs: &Block, lhs: &Block) -> Block {
+    let r = *rhs ^ lhs;
+
+    let mut q: [v128; LEN] = core::array::from_fn(|i| {
+        // SAFETY: `2 * i + 1 < 128` so the 16 bytes read are within `r`, and `v128_load` doesn't
+        // require any alignment.
+        unsafe { v128_load(r.0.as_ptr().add(2 * i).cast()) }
+    });
+
+    // Apply permutations rowwise
+    for i in 0..8 {
+        permute(&mut q, core::array::from_fn(|k| 8 * i + k));
+    }
+
+    // Apply permutations columnwise
+    for i in 0..8 {
+        permute(&mut q, core::array::from_fn(|k| i + 8 * k));
+    }
+
+    let mut out = Block::new();
+    for (i, v) in q.iter().enumerate() {
+        // SAFETY: `2 * i + 1 < 128` so the 16 bytes written are within `out`, and `v128_store`
+        // doesn't require any alignment.
+        unsafe { v128_store(out.0.as_mut_ptr().add(2 * i).cast(), *v) };
+    }
+
+    out ^= &r;
+    out
+}
+
+/// Apply the Argon2 permutation to the 16 words held in the registers at `idx`.
+#[inline(always)]
+fn permute(q: &mut [v128; LEN], idx: [usize; 8]) {
+    let [
+        mut a0,
+        mut a1,
+        mut b0,
+        mut b1,
+        mut c0,
+        mut c1,
+        mut d0,
+        mut d1,
+    ] = idx.map(|i| q[i]);
+
+    g(&mut a0, &mut b0, &mut c0, &mut d0);
+    g(&mut a1, &mut b1, &mut c1, &mut d1);
+
+    // Diagonalize: rotate the lanes of the second, third and fourth row by one, two and three.
+    let mut b0d = u64x2_shuffle::<1, 2>(b0, b1);
+    let mut b1d = u64x2_shuffle::<1, 2>(b1, b0);
+    let mut d0d = u64x2_shuffle::<1, 2>(d1, d0);
+    let mut d1d = u64x2_shuffle::<1, 2>(d0, d1);
+
+    g(&mut a0, &mut b0d, &mut c1, &mut d0d);
+    g(&mut a1, &mut b1d, &mut c0, &mut d1d);
+
+    // Undiagonalize
+    b0 = u64x2_shuffle::<1, 2>(b1d, b0d);
+    b1 = u64x2_shuffle::<1, 2>(b0d, b1d);
+    d0 = u64x2_shuffle::<1, 2>(d0d, d1d);
+    d1 = u64x2_shuffle::<1, 2>(d1d, d0d);
+
+    for (i, v) in idx.into_iter().zip([a0, a1, b0, b1, c0, c1, d0, d1]) {
+        q[i] = v;
+    }
+}
+
+/// BlaMka G function applied to two columns at a time.
+#[inline(always)]
+fn g(a: &mut v128, b: &mut v128, c: &mut v128, d: &mut v128) {
+    *a = blamka(*a, *b);
+    *d = rotr32(v128_xor(*d, *a));
+    *c = blamka(*c, *d);
+    *b = rotr24(v128_xor(*b, *c));
+
+    *a = blamka(*a, *b);
+    *d = rotr16(v128_xor(*d, *a));
+    *c = blamka(*c, *d);
+    *b = rotr63(v128_xor(*b, *c));
+}
+
+/// Compute `x + y + 2 * lo32(x) * lo32(y)` for each 64-bit lane.
+#[inline(always)]
+fn blamka(x: v128, y: v128) -> v128 {
+    let xl = u32x4_shuffle::<0, 2, 0, 2>(x, x);
+    let yl = u32x4_shuffle::<0, 2, 0, 2>(y, y);
+    let z = u64x2_extmul_low_u32x4(xl, yl);
+
+    u64x2_add(u64x2_add(x, y), u64x2_add(z, z))
+}
+
+#[inline(always)]
+fn rotr32(x: v128) -> v128 {
+    u32x4_shuffle::<1, 0, 3, 2>(x, x)
+}
+
+#[inline(always)]
+fn rotr24(x: v128) -> v128 {
+    i8x16_shuffle::<3, 4, 5, 6, 7, 0, 1, 2, 11, 12, 13, 14, 15, 8, 9, 10>(x, x)
+}
+
+#[inline(always)]
+fn rotr16(x: v128) -> v128 {
+    i8x16_shuffle::<2, 3, 4, 5, 6, 7, 0, 1, 10, 11, 12, 13, 14, 15, 8, 9>(x, x)
+}
+
+#[inline(always)]
+fn rotr63(x: v128) -> v128 {
+    v128_or(u64x2_shr(x, 63), u64x2_add(x, x))
+}
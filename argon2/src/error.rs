@@ -14,12 +14,18 @@ pub enum Error {
     /// Algorithm identifier invalid.
     AlgorithmInvalid,
 
+    /// Backend is not available on the current CPU.
+    BackendUnavailable,
+
     /// "B64" encoding is invalid.
     B64Encoding(base64ct::Error),
 
     /// Key ID is too long.
     KeyIdTooLong,
 
+    /// Key ID is not present in the keyring.
+    KeyIdUnknown,
+
     /// Memory cost is too small.
     MemoryTooLittle,
 
@@ -32,6 +38,13 @@ pub enum Error {
     /// Output is too long.
     OutputTooLong,
 
+    /// Parameters exceed the configured [`VerifierLimits`][`crate::VerifierLimits`].
+    ///
+    /// Converted to `password_hash::Error::ParamsInvalid`, as `password_hash` has no error for
+    /// resource limits. Use [`VerifierLimits::check`][`crate::VerifierLimits::check`] directly to
+    /// tell this case apart.
+    ParamsExceedLimits,
+
     /// Password is too long.
     PwdTooLong,
 
@@ -44,6 +57,9 @@ pub enum Error {
     /// Secret is too long.
     SecretTooLong,
 
+    /// Known-answer self-test failed.
+    SelfTestFailed,
+
     /// Not enough threads.
     ThreadsTooFew,
 
@@ -74,16 +90,20 @@ impl fmt::Display for Error {
         f.write_str(match self {
             Error::AdTooLong => "associated data is too long",
             Error::AlgorithmInvalid => "algorithm identifier invalid",
+            Error::BackendUnavailable => "backend is not available",
             Error::B64Encoding(inner) => return write!(f, "B64 encoding invalid: {inner}"),
             Error::KeyIdTooLong => "key ID is too long",
+            Error::KeyIdUnknown => "key ID is unknown",
             Error::MemoryTooLittle => "memory cost is too small",
             Error::MemoryTooMuch => "memory cost is too large",
             Error::OutputTooShort => "output is too short",
             Error::OutputTooLong => "output is too long",
+            Error::ParamsExceedLimits => "parameters exceed limits",
             Error::PwdTooLong => "password is too long",
             Error::SaltTooShort => "salt is too short",
             Error::SaltTooLong => "salt is too long",
             Error::SecretTooLong => "secret is too long",
+            Error::SelfTestFailed => "self-test failed",
             Error::ThreadsTooFew => "not enough threads",
             Error::ThreadsTooMany => "too many threads",
             Error::TimeTooSmall => "time cost is too small",
@@ -113,12 +133,16 @@ impl From<Error> for password_hash::Error {
             Error::AdTooLong => password_hash::Error::ParamInvalid { name: "data" },
             Error::AlgorithmInvalid => password_hash::Error::Algorithm,
             Error::B64Encoding(_) => password_hash::Error::EncodingInvalid,
-            Error::KeyIdTooLong => password_hash::Error::ParamInvalid { name: "keyid" },
+            Error::BackendUnavailable | Error::SelfTestFailed => password_hash::Error::Internal,
+            Error::KeyIdTooLong | Error::KeyIdUnknown => {
+                password_hash::Error::ParamInvalid { name: "keyid" }
+            }
             Error::MemoryTooLittle | Error::MemoryTooMuch => {
                 password_hash::Error::ParamInvalid { name: "m" }
             }
             Error::OutOfMemory => password_hash::Error::OutOfMemory,
             Error::OutputTooShort | Error::OutputTooLong => password_hash::Error::OutputSize,
+            Error::ParamsExceedLimits => password_hash::Error::ParamsInvalid,
             Error::PwdTooLong => password_hash::Error::PasswordInvalid,
             Error::SaltTooShort | Error::SaltTooLong => password_hash::Error::SaltInvalid,
             Error::SecretTooLong => password_hash::Error::ParamsInvalid,
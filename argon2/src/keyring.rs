@@ -0,0 +1,338 @@
+//! Keyring of secret keys ("peppers") selected by key identifier.
+
+use crate::{Argon2, Argon2PasswordHash, Error, KeyId, MAX_SECRET_LEN, Params, Result};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use password_hash::{PasswordHasher, PasswordVerifier, phc::PasswordHash};
+
+/// Argon2 keyring: a set of secret keys (a.k.a. "peppers") indexed by [`KeyId`].
+///
+/// Password hashes produced by the keyring are computed using the current secret key, and record
+/// its identifier in the `keyid` parameter of the PHC string. When verifying a password hash, the
+/// secret key is looked up using the `keyid` parameter of the hash, which allows the pepper to be
+/// rotated by adding a new key and making it current, while hashes computed using older keys
+/// continue to verify.
+///
+/// Password hashes without a `keyid` parameter are rejected by default, as accepting them would
+/// let anyone able to modify stored hashes bypass the pepper by removing their `keyid`. While
+/// migrating hashes computed without a pepper, they can be verified without a secret key by
+/// enabling [`Argon2Keyring::allow_unpeppered`].
+///
+#[cfg_attr(feature = "getrandom", doc = "```")]
+#[cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+/// # fn main() -> Result<(), Box<dyn core::error::Error>> {
+/// use argon2::{Argon2, Argon2Keyring, KeyId, PasswordHasher, PasswordVerifier};
+///
+/// let mut keyring = Argon2Keyring::new(Argon2::default(), KeyId::new(b"key1")?, b"pepper 1")?;
+/// let old_hash = keyring.hash_password(b"hunter42")?;
+///
+/// // Rotate the pepper
+/// keyring.add_key(KeyId::new(b"key2")?, b"pepper 2")?;
+/// keyring.set_current_key(KeyId::new(b"key2")?)?;
+/// let new_hash = keyring.hash_password(b"hunter42")?;
+///
+/// assert_eq!(new_hash.params.get_str("keyid").unwrap(), "a2V5Mg");
+/// assert!(keyring.verify_password(b"hunter42", &old_hash).is_ok());
+/// assert!(keyring.verify_password(b"hunter42", &new_hash).is_ok());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Argon2Keyring<'key> {
+    /// Context providing the algorithm, version, and default params used for hashing
+    argon2: Argon2<'key>,
+
+    /// Secret keys indexed by their identifier
+    keys: BTreeMap<KeyId, &'key [u8]>,
+
+    /// Identifier of the key used to compute new password hashes
+    current: KeyId,
+
+    /// Whether password hashes without a `keyid` are verified without a secret key
+    allow_unpeppered: bool,
+}
+
+impl<'key> Argon2Keyring<'key> {
+    /// Create a new keyring using the algorithm, version, and default params of the provided
+    /// [`Argon2`] context, with the given secret key as the current one.
+    ///
+    /// Any secret key the provided context was constructed with is ignored.
+    ///
+    /// # Errors
+    /// Returns [`Error::SecretTooLong`] in the event `secret` is too long.
+    pub fn new(argon2: Argon2<'key>, keyid: KeyId, secret: &'key [u8]) -> Result<Self> {
+        let mut keyring = Self {
+            argon2,
+            keys: BTreeMap::new(),
+            current: keyid,
+            allow_unpeppered: false,
+        };
+
+        keyring.add_key(keyid, secret)?;
+        Ok(keyring)
+    }
+
+    /// Add a secret key to the keyring, replacing any existing key with the same identifier.
+    ///
+    /// # Errors
+    /// Returns [`Error::SecretTooLong`] in the event `secret` is too long.
+    pub fn add_key(&mut self, keyid: KeyId, secret: &'key [u8]) -> Result<()> {
+        if MAX_SECRET_LEN < secret.len() {
+            return Err(Error::SecretTooLong);
+        }
+
+        self.keys.insert(keyid, secret);
+        Ok(())
+    }
+
+    /// Set the secret key used to compute new password hashes.
+    ///
+    /// # Errors
+    /// Returns [`Error::KeyIdUnknown`] in the event no key with the given identifier has been
+    /// added to the keyring.
+    pub fn set_current_key(&mut self, keyid: KeyId) -> Result<()> {
+        if !self.keys.contains_key(&keyid) {
+            return Err(Error::KeyIdUnknown);
+        }
+
+        self.current = keyid;
+        Ok(())
+    }
+
+    /// Set whether password hashes without a `keyid` parameter are verified without a secret key,
+    /// rather than rejected.
+    ///
+    /// This should only be enabled while migrating password hashes computed without a pepper:
+    /// it allows anyone able to modify stored hashes to bypass the pepper.
+    pub fn allow_unpeppered(&mut self, allow: bool) {
+        self.allow_unpeppered = allow;
+    }
+
+    /// Get the identifier of the secret key used to compute new password hashes.
+    #[must_use]
+    pub fn current_key(&self) -> KeyId {
+        self.current
+    }
+
+    /// Get an [`Argon2`] context which uses the secret key with the given identifier, or no
+    /// secret key if `keyid` is empty and unpeppered hashes are allowed.
+    fn context(&self, keyid: KeyId, params: Params) -> Result<Argon2<'key>> {
+        let secret = if keyid.is_empty() && self.allow_unpeppered {
+            None
+        } else {
+            Some(*self.keys.get(&keyid).ok_or(Error::KeyIdUnknown)?)
+        };
+
+        Ok(self.argon2.with_secret_and_params(secret, params))
+    }
+}
+
+impl fmt::Debug for Argon2Keyring<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argon2Keyring")
+            .field("argon2", &self.argon2)
+            .field("current", &self.current)
+            .field("allow_unpeppered", &self.allow_unpeppered)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PasswordHasher<PasswordHash> for Argon2Keyring<'_> {
+    fn hash_password_with_salt(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+    ) -> password_hash::Result<PasswordHash> {
+        let mut params = self.argon2.params().clone();
+        params.set_keyid(self.current);
+
+        self.context(self.current, params)?
+            .hash_password_with_salt(password, salt)
+    }
+}
+
+impl PasswordVerifier<PasswordHash> for Argon2Keyring<'_> {
+    fn verify_password(&self, password: &[u8], hash: &PasswordHash) -> password_hash::Result<()> {
+        let params = Params::try_from(hash)?;
+        let keyid = KeyId::new(params.keyid())?;
+
+        self.context(keyid, params)?.verify_password(password, hash)
+    }
+}
+
+impl PasswordVerifier<str> for Argon2Keyring<'_> {
+    fn verify_password(&self, password: &[u8], hash: &str) -> password_hash::Result<()> {
+        match PasswordHash::new(hash) {
+            Ok(hash) => self.verify_password(password, &hash),
+            // Fall back to `Argon2PasswordHash` in case the salt is too long for `PasswordHash`
+            Err(err) => match Argon2PasswordHash::new(hash) {
+                Ok(hash) => {
+                    let keyid = KeyId::new(hash.params().keyid())?;
+
+                    self.context(keyid, hash.params().clone())?
+                        .verify_password_phc(password, &hash)
+                }
+                Err(_) => Err(err.into()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::Argon2Keyring;
+    use crate::{
+        Algorithm, Argon2, KeyId, Params, ParamsBuilder, PasswordHasher, PasswordVerifier, Version,
+    };
+    use alloc::string::ToString;
+    use password_hash::Error;
+
+    /// Example password only: don't use this as a real password!!!
+    const EXAMPLE_PASSWORD: &[u8] = b"hunter42";
+
+    /// Example salt value. Don't use a static salt value!!!
+    const EXAMPLE_SALT: &[u8] = b"example-salt";
+
+    fn keyring() -> Argon2Keyring<'static> {
+        let argon2 = Argon2::from(Params::new(32, 1, 1, None).unwrap());
+        let mut keyring =
+            Argon2Keyring::new(argon2, KeyId::new(b"old").unwrap(), b"old pepper").unwrap();
+        keyring
+            .add_key(KeyId::new(b"new").unwrap(), b"new pepper")
+            .unwrap();
+        keyring
+    }
+
+    #[test]
+    fn hash_records_current_keyid() {
+        let mut keyring = keyring();
+        keyring
+            .set_current_key(KeyId::new(b"new").unwrap())
+            .unwrap();
+
+        let hash = keyring
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        assert_eq!(hash.params.get_str("keyid").unwrap(), "bmV3");
+        assert_eq!(keyring.verify_password(EXAMPLE_PASSWORD, &hash), Ok(()));
+        assert_eq!(
+            keyring.verify_password(b"invalid", &hash),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn rotated_key_still_verifies() {
+        let mut keyring = keyring();
+        let hash = keyring
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        keyring
+            .set_current_key(KeyId::new(b"new").unwrap())
+            .unwrap();
+        assert_eq!(keyring.verify_password(EXAMPLE_PASSWORD, &hash), Ok(()));
+
+        // The pepper is actually used: the hash doesn't verify without it
+        assert_eq!(
+            Argon2::default().verify_password(EXAMPLE_PASSWORD, &hash),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn unknown_keyid() {
+        let mut keyring = keyring();
+        assert_eq!(
+            keyring.set_current_key(KeyId::new(b"unknown").unwrap()),
+            Err(crate::Error::KeyIdUnknown)
+        );
+
+        let other = Argon2Keyring::new(
+            Argon2::from(Params::new(32, 1, 1, None).unwrap()),
+            KeyId::new(b"other").unwrap(),
+            b"other pepper",
+        )
+        .unwrap();
+        let hash = other
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        assert_eq!(
+            keyring.verify_password(EXAMPLE_PASSWORD, &hash),
+            Err(Error::ParamInvalid { name: "keyid" })
+        );
+    }
+
+    #[test]
+    fn hash_without_keyid_rejected_by_default() {
+        let hash = Argon2::from(Params::new(32, 1, 1, None).unwrap())
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        assert_eq!(
+            keyring().verify_password(EXAMPLE_PASSWORD, &hash),
+            Err(Error::ParamInvalid { name: "keyid" })
+        );
+    }
+
+    #[test]
+    fn hash_with_keyid_removed_rejected() {
+        let keyring = keyring();
+        let hash = keyring
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap()
+            .to_string();
+        let stripped = hash.replace(",keyid=b2xk", "");
+        assert_ne!(hash, stripped);
+
+        assert_eq!(
+            keyring.verify_password(EXAMPLE_PASSWORD, stripped.as_str()),
+            Err(Error::ParamInvalid { name: "keyid" })
+        );
+    }
+
+    #[test]
+    fn long_salt_verifies() {
+        let params = ParamsBuilder::new()
+            .m_cost(32)
+            .t_cost(1)
+            .p_cost(1)
+            .keyid(KeyId::new(b"old").unwrap())
+            .build()
+            .unwrap();
+        let hash = Argon2::new_with_secret(
+            b"old pepper",
+            Algorithm::default(),
+            Version::default(),
+            params,
+        )
+        .unwrap()
+        .hash_password_phc(EXAMPLE_PASSWORD, &[0x5a; 64])
+        .unwrap()
+        .to_string();
+
+        let keyring = keyring();
+        assert_eq!(
+            keyring.verify_password(EXAMPLE_PASSWORD, hash.as_str()),
+            Ok(())
+        );
+        assert_eq!(
+            keyring.verify_password(b"invalid", hash.as_str()),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn hash_without_keyid_verifies_when_allowed() {
+        let hash = Argon2::from(Params::new(32, 1, 1, None).unwrap())
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        let mut keyring = keyring();
+        keyring.allow_unpeppered(true);
+        assert_eq!(keyring.verify_password(EXAMPLE_PASSWORD, &hash), Ok(()));
+    }
+}
@@ -133,21 +133,37 @@ compile_error!("this crate builds on 32-bit and 64-bit platforms only");
 extern crate alloc;
 
 mod algorithm;
+mod backend;
+#[cfg(feature = "alloc")]
+mod batch;
 mod blake2b_long;
 mod block;
 mod error;
+#[cfg(all(feature = "alloc", feature = "password-hash"))]
+mod keyring;
 mod memory;
 mod params;
+#[cfg(all(feature = "alloc", feature = "password-hash"))]
+mod phc_hash;
+mod self_test;
 mod version;
 
 pub use crate::{
-    algorithm::Algorithm,
+    algorithm::{Algorithm, AlgorithmPolicy},
+    backend::Backend,
     block::Block,
     error::{Error, Result},
-    params::{AssociatedData, KeyId, Params, ParamsBuilder},
+    params::{AssociatedData, KeyId, Params, ParamsBuilder, VerifierLimits},
+    self_test::self_test,
     version::Version,
 };
 
+#[cfg(feature = "alloc")]
+pub use crate::batch::BatchOptions;
+#[cfg(feature = "zeroize")]
+pub use crate::block::ZeroizingBlocks;
+#[cfg(all(feature = "alloc", feature = "password-hash"))]
+pub use crate::{keyring::Argon2Keyring, phc_hash::Argon2PasswordHash};
 #[cfg(feature = "kdf")]
 pub use kdf::{self, Kdf, Pbkdf};
 #[cfg(feature = "password-hash")]
@@ -158,7 +174,10 @@ pub use {
     },
 };
 
-use crate::blake2b_long::blake2b_long;
+use crate::{
+    backend::{CompressMany, ResolvedBackend},
+    blake2b_long::blake2b_long,
+};
 use blake2::{Blake2b512, Digest, digest};
 use core::fmt;
 use memory::Memory;
@@ -190,9 +209,6 @@ pub(crate) const SYNC_POINTS: usize = 4;
 /// To generate reference block positions
 const ADDRESSES_IN_BLOCK: usize = 128;
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-cpufeatures::new!(avx2_cpuid, "avx2");
-
 /// Argon2 context.
 ///
 /// This is the primary type of this crate's API, and contains the following:
@@ -215,8 +231,14 @@ pub struct Argon2<'key> {
     /// Key array
     secret: Option<&'key [u8]>,
 
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    cpu_feat_avx2: avx2_cpuid::InitToken,
+    /// Policy for algorithms selected by input
+    algorithm_policy: AlgorithmPolicy,
+
+    /// Limits for params selected by input
+    verifier_limits: VerifierLimits,
+
+    /// Backend forced for the compression function, if any
+    backend: Option<Backend>,
 }
 
 impl Default for Argon2<'_> {
@@ -231,21 +253,46 @@ impl fmt::Debug for Argon2<'_> {
             .field("algorithm", &self.algorithm)
             .field("version", &self.version)
             .field("params", &self.params)
+            .field("algorithm_policy", &self.algorithm_policy)
+            .field("verifier_limits", &self.verifier_limits)
+            .field("backend", &self.backend)
             .finish_non_exhaustive()
     }
 }
 
 impl<'key> Argon2<'key> {
     /// Create a new Argon2 context.
+    ///
+    /// This is a `const fn`, so contexts can be defined as `static`s, with invalid [`Params`]
+    /// rejected at compile time. CPU features are detected on first use.
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use argon2::{Algorithm, Argon2, Params, Version};
+    ///
+    /// pub static HASHER: Argon2<'static> = Argon2::new(
+    ///     Algorithm::Argon2id,
+    ///     Version::V0x13,
+    ///     match Params::new(19 * 1024, 2, 1, None) {
+    ///         Ok(params) => params,
+    ///         Err(_) => panic!("invalid Argon2 params"),
+    ///     },
+    /// );
+    ///
+    /// let mut output_key_material = [0u8; 32];
+    /// HASHER.hash_password_into(b"hunter42", b"example salt", &mut output_key_material)?;
+    /// # Ok::<(), argon2::Error>(())
+    /// ```
     #[must_use]
-    pub fn new(algorithm: Algorithm, version: Version, params: Params) -> Self {
+    pub const fn new(algorithm: Algorithm, version: Version, params: Params) -> Self {
         Self {
             algorithm,
             version,
             params,
             secret: None,
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            cpu_feat_avx2: avx2_cpuid::init(),
+            algorithm_policy: AlgorithmPolicy::AllowAll,
+            verifier_limits: VerifierLimits::NONE,
+            backend: None,
         }
     }
 
@@ -253,7 +300,7 @@ impl<'key> Argon2<'key> {
     ///
     /// # Errors
     /// Returns [`Error::SecretTooLong`] in the event `secret` is too long.
-    pub fn new_with_secret(
+    pub const fn new_with_secret(
         secret: &'key [u8],
         algorithm: Algorithm,
         version: Version,
@@ -268,11 +315,67 @@ impl<'key> Argon2<'key> {
             version,
             params,
             secret: Some(secret),
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            cpu_feat_avx2: avx2_cpuid::init(),
+            algorithm_policy: AlgorithmPolicy::AllowAll,
+            verifier_limits: VerifierLimits::NONE,
+            backend: None,
         })
     }
 
+    /// Set the [`AlgorithmPolicy`] restricting which algorithms this context will compute when
+    /// the algorithm is selected by its input, e.g. the algorithm identifier of a PHC string
+    /// hash being verified.
+    ///
+    /// Hashes whose algorithm isn't permitted by the policy are rejected with
+    /// [`password_hash::Error::Algorithm`] before any work is done.
+    #[must_use]
+    pub const fn with_algorithm_policy(mut self, policy: AlgorithmPolicy) -> Self {
+        self.algorithm_policy = policy;
+        self
+    }
+
+    /// Get the configured [`AlgorithmPolicy`].
+    #[must_use]
+    pub const fn algorithm_policy(&self) -> AlgorithmPolicy {
+        self.algorithm_policy
+    }
+
+    /// Set the [`VerifierLimits`] on the params this context accepts when they are selected by
+    /// its input, e.g. the params of a PHC string hash being verified.
+    ///
+    /// Hashes whose params exceed the limits are rejected with
+    /// [`password_hash::Error::ParamsInvalid`] before any memory is allocated.
+    #[must_use]
+    pub const fn with_verifier_limits(mut self, limits: VerifierLimits) -> Self {
+        self.verifier_limits = limits;
+        self
+    }
+
+    /// Get the configured [`VerifierLimits`].
+    #[must_use]
+    pub const fn verifier_limits(&self) -> VerifierLimits {
+        self.verifier_limits
+    }
+
+    /// Force the compression function to be computed using the given [`Backend`], rather than
+    /// the fastest one available, e.g. for benchmarking or to reproduce a bug.
+    ///
+    /// # Errors
+    /// Returns [`Error::BackendUnavailable`] if `backend` isn't available on the current CPU.
+    pub fn with_backend(mut self, backend: Backend) -> Result<Self> {
+        if !backend.is_available() {
+            return Err(Error::BackendUnavailable);
+        }
+
+        self.backend = Some(backend);
+        Ok(self)
+    }
+
+    /// Get the [`Backend`] used to compute the compression function.
+    #[must_use]
+    pub fn backend(&self) -> Backend {
+        self.backend.unwrap_or_else(Backend::detect)
+    }
+
     /// Hash a password and associated parameters into the provided output buffer.
     ///
     /// # Errors
@@ -288,6 +391,118 @@ impl<'key> Argon2<'key> {
         self.hash_password_into_with_memory(pwd, salt, out, blocks.as_slice())
     }
 
+    /// Hash many passwords with the same params, reusing memory between hashes rather than
+    /// allocating it anew for each one.
+    ///
+    /// `inputs` is a slice of `(password, salt)` pairs, and the hash of each pair is written to the
+    /// output buffer at the same index in `outs`.
+    ///
+    /// `options` controls how many threads are used and bounds the total memory: see
+    /// [`BatchOptions`]. Each thread allocates memory once, for one hash or for two if interleaving
+    /// is enabled, and computes its share of the hashes one after the other.
+    ///
+    /// # Errors
+    /// - Returns [`Error::OutputTooShort`] if `outs` has fewer elements than `inputs`.
+    /// - Returns [`Error::OutputTooLong`] if `outs` has more elements than `inputs`.
+    /// - Returns [`Error::OutOfMemory`] if memory couldn't be allocated.
+    /// - Returns an error encountered while hashing, as described for
+    ///   [`Argon2::hash_password_into`].
+    #[cfg(feature = "alloc")]
+    pub fn hash_many(
+        &self,
+        inputs: &[(&[u8], &[u8])],
+        outs: &mut [&mut [u8]],
+        options: BatchOptions,
+    ) -> Result<()> {
+        if outs.len() < inputs.len() {
+            return Err(Error::OutputTooShort);
+        }
+
+        if outs.len() > inputs.len() {
+            return Err(Error::OutputTooLong);
+        }
+
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        let block_count = self.params.block_count();
+        let plan = options.plan(inputs.len(), block_count);
+
+        // Memory for two hashes fits within `options.max_memory` when interleaving
+        let memory_len = if plan.interleave {
+            2 * block_count
+        } else {
+            block_count
+        };
+
+        let hash_chunk = |inputs: &[(&[u8], &[u8])], outs: &mut [&mut [u8]]| {
+            let mut blocks = block::Blocks::new(memory_len).ok_or(Error::OutOfMemory)?;
+            let (memory, second_memory) = blocks.as_slice().split_at_mut(block_count);
+
+            let mut items = inputs.iter().zip(outs);
+            while let Some((&(pwd, salt), out)) = items.next() {
+                let second = if plan.interleave { items.next() } else { None };
+
+                match second {
+                    Some((&(second_pwd, second_salt), second_out)) => self.hash_password_into_x2(
+                        [(pwd, salt), (second_pwd, second_salt)],
+                        [out, second_out],
+                        [&mut *memory, &mut *second_memory],
+                    )?,
+                    None => self.hash_password_into_with_memory(pwd, salt, out, &mut *memory)?,
+                }
+            }
+
+            Ok(())
+        };
+
+        let chunk_len = inputs.len().div_ceil(plan.threads);
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            inputs
+                .chunks(chunk_len)
+                .zip(outs.chunks_mut(chunk_len))
+                .try_for_each(|(inputs, outs)| hash_chunk(inputs, outs))
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::{
+                iter::{IndexedParallelIterator, ParallelIterator},
+                slice::{ParallelSlice, ParallelSliceMut},
+            };
+
+            inputs
+                .par_chunks(chunk_len)
+                .zip(outs.par_chunks_mut(chunk_len))
+                .try_for_each(|(inputs, outs)| hash_chunk(inputs, outs))
+        }
+    }
+
+    /// Hash two passwords in lockstep, interleaving their compressions.
+    #[cfg(feature = "alloc")]
+    fn hash_password_into_x2(
+        &self,
+        inputs: [(&[u8], &[u8]); 2],
+        outs: [&mut [u8]; 2],
+        memory_blocks: [&mut [Block]; 2],
+    ) -> Result<()> {
+        for ((pwd, salt), out) in inputs.iter().zip(&outs) {
+            self.verify_output(out)?;
+            Self::verify_inputs(pwd, salt)?;
+        }
+
+        let initial_hashes = [0, 1].map(|i| self.initial_hash(inputs[i].0, inputs[i].1, outs[i]));
+        let [memory, second_memory] = memory_blocks;
+        self.fill_blocks([&mut *memory, &mut *second_memory], initial_hashes)?;
+
+        let [out, second_out] = outs;
+        self.finalize(memory, out)?;
+        self.finalize(second_memory, second_out)
+    }
+
     /// Hash a password and associated parameters into the provided output buffer.
     ///
     /// This method takes an explicit `memory_blocks` parameter which allows
@@ -311,20 +526,12 @@ impl<'key> Argon2<'key> {
         out: &mut [u8],
         mut memory_blocks: impl AsMut<[Block]>,
     ) -> Result<()> {
-        // Validate output length
-        if out.len() < self.params.output_len().unwrap_or(Params::MIN_OUTPUT_LEN) {
-            return Err(Error::OutputTooShort);
-        }
-
-        if out.len() > self.params.output_len().unwrap_or(Params::MAX_OUTPUT_LEN) {
-            return Err(Error::OutputTooLong);
-        }
-
+        self.verify_output(out)?;
         Self::verify_inputs(pwd, salt)?;
 
         // Hashing all inputs
         let initial_hash = self.initial_hash(pwd, salt, out);
-        self.fill_blocks(memory_blocks.as_mut(), initial_hash)?;
+        self.fill_blocks([memory_blocks.as_mut()], [initial_hash])?;
         self.finalize(memory_blocks.as_mut(), out)
     }
 
@@ -347,51 +554,65 @@ impl<'key> Argon2<'key> {
         Self::verify_inputs(pwd, salt)?;
 
         let initial_hash = self.initial_hash(pwd, salt, &[]);
-        self.fill_blocks(memory_blocks.as_mut(), initial_hash)
+        self.fill_blocks([memory_blocks.as_mut()], [initial_hash])
     }
 
+    /// Fill the memory blocks of `N` hashes with the same params in lockstep, computing their
+    /// compressions together.
     #[allow(clippy::cast_possible_truncation, unused_mut)]
-    fn fill_blocks(
+    fn fill_blocks<const N: usize>(
         &self,
-        memory_blocks: &mut [Block],
-        mut initial_hash: digest::Output<Blake2b512>,
-    ) -> Result<()> {
+        memory_blocks: [&mut [Block]; N],
+        mut initial_hashes: [digest::Output<Blake2b512>; N],
+    ) -> Result<()>
+    where
+        ResolvedBackend: CompressMany<N>,
+    {
         let block_count = self.params.block_count();
-        let mut memory_blocks = memory_blocks
-            .get_mut(..block_count)
-            .ok_or(Error::MemoryTooLittle)?;
+        if memory_blocks
+            .iter()
+            .any(|blocks| blocks.len() < block_count)
+        {
+            return Err(Error::MemoryTooLittle);
+        }
+
+        let mut memory_blocks = memory_blocks.map(|blocks| &mut blocks[..block_count]);
 
         let segment_length = self.params.segment_length();
         let iterations = self.params.t_cost() as usize;
         let lane_length = self.params.lane_length();
         let lanes = self.params.lanes();
 
-        // Initialize the first two blocks in each lane
-        for (l, lane) in memory_blocks.chunks_exact_mut(lane_length).enumerate() {
-            for (i, block) in lane[..2].iter_mut().enumerate() {
-                let i = i as u32;
-                let l = l as u32;
-
-                // Make the first and second block in each lane as G(H0||0||i) or
-                // G(H0||1||i)
-                let inputs = &[
-                    initial_hash.as_ref(),
-                    &i.to_le_bytes()[..],
-                    &l.to_le_bytes()[..],
-                ];
-
-                let mut hash = [0u8; Block::SIZE];
-                blake2b_long(inputs, &mut hash)?;
-                block.load(&hash);
+        for (memory_blocks, initial_hash) in memory_blocks.iter_mut().zip(&mut initial_hashes) {
+            // Initialize the first two blocks in each lane
+            for (l, lane) in memory_blocks.chunks_exact_mut(lane_length).enumerate() {
+                for (i, block) in lane[..2].iter_mut().enumerate() {
+                    let i = i as u32;
+                    let l = l as u32;
+
+                    // Make the first and second block in each lane as G(H0||0||i) or
+                    // G(H0||1||i)
+                    let inputs = &[
+                        initial_hash.as_ref(),
+                        &i.to_le_bytes()[..],
+                        &l.to_le_bytes()[..],
+                    ];
+
+                    let mut hash = [0u8; Block::SIZE];
+                    blake2b_long(inputs, &mut hash)?;
+                    block.load(&hash);
+                }
             }
+
+            #[cfg(feature = "zeroize")]
+            initial_hash.zeroize();
         }
 
-        #[cfg(feature = "zeroize")]
-        initial_hash.zeroize();
+        let backend = self.backend().resolve();
 
         // Run passes on blocks
         for pass in 0..iterations {
-            memory_blocks.for_each_segment(lanes, |mut memory_view, slice, lane| {
+            memory_blocks.for_each_segment(lanes, |mut memory_views, slice, lane| {
                 let data_independent_addressing = self.algorithm == Algorithm::Argon2i
                     || (self.algorithm == Algorithm::Argon2id
                         && pass == 0
@@ -415,7 +636,8 @@ impl<'key> Argon2<'key> {
                 let first_block = if pass == 0 && slice == 0 {
                     if data_independent_addressing {
                         // Generate first set of addresses
-                        self.update_address_block(
+                        Self::update_address_block(
+                            backend,
                             &mut address_block,
                             &mut input_block,
                             &zero_block,
@@ -439,80 +661,87 @@ impl<'key> Argon2<'key> {
 
                 // Fill blocks in the segment
                 for block in first_block..segment_length {
-                    // Extract entropy
-                    let rand = if data_independent_addressing {
-                        let address_index = block % ADDRESSES_IN_BLOCK;
-
-                        if address_index == 0 {
-                            self.update_address_block(
-                                &mut address_block,
-                                &mut input_block,
-                                &zero_block,
-                            );
-                        }
-
-                        address_block.as_ref()[address_index]
-                    } else {
-                        memory_view.get_block(prev_index).as_ref()[0]
-                    };
-
-                    // Calculate source block index for compress function
-                    let ref_lane = if pass == 0 && slice == 0 {
-                        // Cannot reference other lanes yet
-                        lane
-                    } else {
-                        (rand >> 32) as usize % lanes
-                    };
-
-                    let reference_area_size = if pass == 0 {
-                        // First pass
-                        if slice == 0 {
-                            // First slice
-                            block - 1 // all but the previous
-                        } else if ref_lane == lane {
-                            // The same lane => add current segment
-                            slice * segment_length + block - 1
+                    let address_index = block % ADDRESSES_IN_BLOCK;
+
+                    if data_independent_addressing && address_index == 0 {
+                        Self::update_address_block(
+                            backend,
+                            &mut address_block,
+                            &mut input_block,
+                            &zero_block,
+                        );
+                    }
+
+                    let ref_indices = memory_views.each_ref().map(|memory_view| {
+                        // Extract entropy
+                        let rand = if data_independent_addressing {
+                            address_block.as_ref()[address_index]
                         } else {
-                            slice * segment_length - if block == 0 { 1 } else { 0 }
-                        }
-                    } else {
-                        // Second pass
-                        if ref_lane == lane {
-                            lane_length - segment_length + block - 1
+                            memory_view.get_block(prev_index).as_ref()[0]
+                        };
+
+                        // Calculate source block index for compress function
+                        let ref_lane = if pass == 0 && slice == 0 {
+                            // Cannot reference other lanes yet
+                            lane
                         } else {
-                            lane_length - segment_length - if block == 0 { 1 } else { 0 }
-                        }
-                    };
-
-                    // 1.2.4. Mapping rand to 0..<reference_area_size-1> and produce
-                    // relative position
-                    let mut map = rand & 0xFFFFFFFF;
-                    map = (map * map) >> 32;
-                    let relative_position = reference_area_size
-                        - 1
-                        - ((reference_area_size as u64 * map) >> 32) as usize;
-
-                    // 1.2.5 Computing starting position
-                    let start_position = if pass != 0 && slice != SYNC_POINTS - 1 {
-                        (slice + 1) * segment_length
-                    } else {
-                        0
-                    };
-
-                    let lane_index = (start_position + relative_position) % lane_length;
-                    let ref_index = ref_lane * lane_length + lane_index;
-
-                    // Calculate new block
-                    let result = self.compress(
-                        memory_view.get_block(prev_index),
-                        memory_view.get_block(ref_index),
+                            (rand >> 32) as usize % lanes
+                        };
+
+                        let reference_area_size = if pass == 0 {
+                            // First pass
+                            if slice == 0 {
+                                // First slice
+                                block - 1 // all but the previous
+                            } else if ref_lane == lane {
+                                // The same lane => add current segment
+                                slice * segment_length + block - 1
+                            } else {
+                                slice * segment_length - if block == 0 { 1 } else { 0 }
+                            }
+                        } else {
+                            // Second pass
+                            if ref_lane == lane {
+                                lane_length - segment_length + block - 1
+                            } else {
+                                lane_length - segment_length - if block == 0 { 1 } else { 0 }
+                            }
+                        };
+
+                        // 1.2.4. Mapping rand to 0..<reference_area_size-1> and produce
+                        // relative position
+                        let mut map = rand & 0xFFFFFFFF;
+                        map = (map * map) >> 32;
+                        let relative_position = reference_area_size
+                            - 1
+                            - ((reference_area_size as u64 * map) >> 32) as usize;
+
+                        // 1.2.5 Computing starting position
+                        let start_position = if pass != 0 && slice != SYNC_POINTS - 1 {
+                            (slice + 1) * segment_length
+                        } else {
+                            0
+                        };
+
+                        let lane_index = (start_position + relative_position) % lane_length;
+                        ref_lane * lane_length + lane_index
+                    });
+
+                    // Calculate new blocks
+                    let results = backend.compress_many(
+                        memory_views
+                            .each_ref()
+                            .map(|memory_view| memory_view.get_block(prev_index)),
+                        core::array::from_fn(|k| memory_views[k].get_block(ref_indices[k])),
                     );
 
-                    if self.version == Version::V0x10 || pass == 0 {
-                        *memory_view.get_block_mut(cur_index) = result;
-                    } else {
-                        *memory_view.get_block_mut(cur_index) ^= &result;
-                    };
+                    for (memory_view, result) in memory_views.iter_mut().zip(&results) {
+                        if self.version == Version::V0x10 || pass == 0 {
+                            *memory_view.get_block_mut(cur_index) = *result;
+                        } else {
+                            *memory_view.get_block_mut(cur_index) ^= result;
+                        };
+                    }
 
                     prev_index = cur_index;
                     cur_index += 1;
@@ -523,28 +752,111 @@ impl<'key> Argon2<'key> {
         Ok(())
     }
 
-    fn compress(&self, rhs: &Block, lhs: &Block) -> Block {
-        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-        {
-            /// Enable AVX2 optimizations.
-            #[target_feature(enable = "avx2")]
-            unsafe fn compress_avx2(rhs: &Block, lhs: &Block) -> Block {
-                Block::compress(rhs, lhs)
-            }
+    /// Get default configured [`Params`].
+    #[must_use]
+    pub const fn params(&self) -> &Params {
+        &self.params
+    }
 
-            if self.cpu_feat_avx2.get() {
-                // SAFETY: checked that AVX2 was detected.
-                return unsafe { compress_avx2(rhs, lhs) };
-            }
+    /// Hash a password into an [`Argon2PasswordHash`], which supports salts of up to
+    /// [`Argon2PasswordHash::MAX_SALT_LEN`] bytes, unlike [`PasswordHash`].
+    ///
+    /// # Errors
+    /// - Returns [`password_hash::Error::SaltInvalid`] if `salt` is too short or too long.
+    /// - Returns [`password_hash::Error::OutputSize`] if the configured output length isn't
+    ///   supported in PHC strings.
+    /// - Propagates errors from [`Argon2::hash_password_into`].
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    pub fn hash_password_phc(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+    ) -> password_hash::Result<Argon2PasswordHash> {
+        if salt.len() > Argon2PasswordHash::MAX_SALT_LEN {
+            return Err(password_hash::Error::SaltInvalid);
         }
 
-        Block::compress(rhs, lhs)
+        let output_len = self
+            .params
+            .output_len()
+            .unwrap_or(Params::DEFAULT_OUTPUT_LEN);
+
+        let mut buffer = [0u8; Output::MAX_LENGTH];
+        let out = buffer
+            .get_mut(..output_len)
+            .ok_or(password_hash::Error::OutputSize)?;
+
+        self.hash_password_into(password, salt, out)?;
+
+        Argon2PasswordHash::from_parts(self.algorithm, self.version, self.params.clone(), salt, out)
     }
 
-    /// Get default configured [`Params`].
-    #[must_use]
-    pub const fn params(&self) -> &Params {
-        &self.params
+    /// Verify a password against an [`Argon2PasswordHash`].
+    ///
+    /// The algorithm, version and params of the hash are used instead of those configured in
+    /// this context, subject to its [`AlgorithmPolicy`] and [`VerifierLimits`].
+    ///
+    /// # Errors
+    /// - Returns [`password_hash::Error::PasswordInvalid`] if the password doesn't match.
+    /// - Returns [`password_hash::Error::Algorithm`] if the algorithm isn't permitted.
+    /// - Returns [`password_hash::Error::ParamsInvalid`] if the params exceed the limits.
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    pub fn verify_password_phc(
+        &self,
+        password: &[u8],
+        hash: &Argon2PasswordHash,
+    ) -> password_hash::Result<()> {
+        let computed = self
+            .input_context(hash.algorithm(), hash.version(), hash.params().clone())?
+            .hash_password_phc(password, hash.salt())?;
+
+        // Hash outputs are compared in constant time by `Output`
+        if computed == *hash {
+            Ok(())
+        } else {
+            Err(password_hash::Error::PasswordInvalid)
+        }
+    }
+
+    /// Get a context for the algorithm, version and params selected by input, e.g. a PHC string
+    /// hash being verified, after checking them against the configured [`AlgorithmPolicy`] and
+    /// [`VerifierLimits`].
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    fn input_context(
+        &self,
+        algorithm: Algorithm,
+        version: Version,
+        params: Params,
+    ) -> password_hash::Result<Self> {
+        if !self.algorithm_policy.permits(algorithm) {
+            return Err(password_hash::Error::Algorithm);
+        }
+
+        self.verifier_limits.check(&params)?;
+
+        Ok(Self {
+            secret: self.secret,
+            algorithm,
+            version,
+            params,
+            algorithm_policy: self.algorithm_policy,
+            verifier_limits: self.verifier_limits,
+            backend: self.backend,
+        })
+    }
+
+    /// Clone this context, replacing its secret key and default [`Params`].
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    pub(crate) fn with_secret_and_params(
+        &self,
+        secret: Option<&'key [u8]>,
+        params: Params,
+    ) -> Self {
+        Self {
+            secret,
+            params,
+            ..self.clone()
+        }
     }
 
     fn finalize(&self, memory_blocks: &[Block], out: &mut [u8]) -> Result<()> {
@@ -577,14 +889,14 @@ impl<'key> Argon2<'key> {
     }
 
     fn update_address_block(
-        &self,
+        backend: ResolvedBackend,
         address_block: &mut Block,
         input_block: &mut Block,
         zero_block: &Block,
     ) {
         input_block.as_mut()[6] += 1;
-        *address_block = self.compress(zero_block, input_block);
-        *address_block = self.compress(zero_block, address_block);
+        *address_block = backend.compress(zero_block, input_block);
+        *address_block = backend.compress(zero_block, address_block);
     }
 
     /// Hashes all the inputs into `blockhash[PREHASH_DIGEST_LEN]`.
@@ -614,6 +926,18 @@ impl<'key> Argon2<'key> {
         digest.finalize()
     }
 
+    fn verify_output(&self, out: &[u8]) -> Result<()> {
+        if out.len() < self.params.output_len().unwrap_or(Params::MIN_OUTPUT_LEN) {
+            return Err(Error::OutputTooShort);
+        }
+
+        if out.len() > self.params.output_len().unwrap_or(Params::MAX_OUTPUT_LEN) {
+            return Err(Error::OutputTooLong);
+        }
+
+        Ok(())
+    }
+
     const fn verify_inputs(pwd: &[u8], salt: &[u8]) -> Result<()> {
         if pwd.len() > MAX_PWD_LEN {
             return Err(Error::PwdTooLong);
@@ -665,15 +989,8 @@ impl CustomizedPasswordHasher<PasswordHash> for Argon2<'_> {
             .transpose()?
             .unwrap_or_default();
 
-        Self {
-            secret: self.secret,
-            algorithm,
-            version,
-            params,
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            cpu_feat_avx2: self.cpu_feat_avx2,
-        }
-        .hash_password_with_salt(password, salt)
+        self.input_context(algorithm, version, params)?
+            .hash_password_with_salt(password, salt)
     }
 }
 
@@ -712,7 +1029,14 @@ impl PasswordHasher<PasswordHash> for Argon2<'_> {
 #[cfg(all(feature = "alloc", feature = "password-hash"))]
 impl PasswordVerifier<str> for Argon2<'_> {
     fn verify_password(&self, password: &[u8], hash: &str) -> password_hash::Result<()> {
-        self.verify_password(password, &PasswordHash::new(hash)?)
+        match PasswordHash::new(hash) {
+            Ok(hash) => self.verify_password(password, &hash),
+            // Fall back to `Argon2PasswordHash` in case the salt is too long for `PasswordHash`
+            Err(err) => match Argon2PasswordHash::new(hash) {
+                Ok(hash) => self.verify_password_phc(password, &hash),
+                Err(_) => Err(err.into()),
+            },
+        }
     }
 }
 
@@ -732,8 +1056,8 @@ impl From<&Params> for Argon2<'_> {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use crate::{
-        Algorithm, Argon2, CustomizedPasswordHasher, Params, PasswordHasher, PasswordVerifier,
-        Version,
+        Algorithm, AlgorithmPolicy, Argon2, BatchOptions, Block, CustomizedPasswordHasher, Params,
+        PasswordHasher, PasswordVerifier, VerifierLimits, Version,
     };
 
     /// Example password only: don't use this as a real password!!!
@@ -794,4 +1118,90 @@ mod tests {
             Ok(())
         );
     }
+
+    #[test]
+    fn algorithm_policy_rejects_argon2d() {
+        let params = Params::new(8, 1, 1, None).unwrap();
+        let hash = Argon2::new(Algorithm::Argon2d, Version::V0x13, params)
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        assert_eq!(
+            Argon2::default().verify_password(EXAMPLE_PASSWORD, &hash),
+            Ok(())
+        );
+
+        let verifier =
+            Argon2::default().with_algorithm_policy(AlgorithmPolicy::SideChannelResistant);
+        assert_eq!(
+            verifier.verify_password(EXAMPLE_PASSWORD, &hash),
+            Err(password_hash::Error::Algorithm)
+        );
+    }
+
+    #[test]
+    fn hash_many_matches_hash_password_into() {
+        let params = Params::new(32, 2, 2, Some(32)).unwrap();
+        let memory = params.block_count() * Block::SIZE;
+        let inputs: [(&[u8], &[u8]); 5] = [
+            (b"password 1", b"example salt 1"),
+            (b"password 2", b"example salt 2"),
+            (b"password 3", b"example salt 3"),
+            (b"password 4", b"example salt 4"),
+            (b"password 5", b"example salt 5"),
+        ];
+
+        for algorithm in [Algorithm::Argon2d, Algorithm::Argon2i, Algorithm::Argon2id] {
+            let argon2 = Argon2::new(algorithm, Version::default(), params.clone());
+
+            let expected = inputs.map(|(pwd, salt)| {
+                let mut out = [0u8; 32];
+                argon2.hash_password_into(pwd, salt, &mut out).unwrap();
+                out
+            });
+
+            for options in [
+                BatchOptions::SEQUENTIAL,
+                BatchOptions::SEQUENTIAL.with_interleave(true),
+                BatchOptions::new(2),
+                BatchOptions::new(2).with_interleave(true),
+                BatchOptions::UNBOUNDED.with_max_memory(memory),
+                BatchOptions::UNBOUNDED.with_interleave(true),
+            ] {
+                let mut outs = [[0u8; 32]; 5];
+                let mut out_refs = outs.each_mut().map(|out| &mut out[..]);
+                assert_eq!(
+                    argon2.hash_many(&inputs, &mut out_refs[..4], options),
+                    Err(crate::Error::OutputTooShort)
+                );
+
+                argon2.hash_many(&inputs, &mut out_refs, options).unwrap();
+                assert_eq!(outs, expected, "{algorithm:?} {options:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn verifier_limits_reject_expensive_params() {
+        let params = Params::new(64, 2, 1, None).unwrap();
+        let hash = Argon2::from(params)
+            .hash_password_with_salt(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+
+        let verifier = Argon2::default().with_verifier_limits(VerifierLimits {
+            max_m_cost: 32,
+            ..VerifierLimits::default()
+        });
+        assert_eq!(
+            verifier.verify_password(EXAMPLE_PASSWORD, &hash),
+            Err(password_hash::Error::ParamsInvalid)
+        );
+
+        let verifier = Argon2::default().with_verifier_limits(VerifierLimits {
+            max_m_cost: 64,
+            max_t_cost: 2,
+            max_p_cost: 1,
+        });
+        assert_eq!(verifier.verify_password(EXAMPLE_PASSWORD, &hash), Ok(()));
+    }
 }
@@ -18,31 +18,34 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{Block, SYNC_POINTS};
 
-/// Extension trait for Argon2 memory blocks.
-pub(crate) trait Memory<'a> {
-    /// Compute each Argon2 segment.
+/// Extension trait for the memory blocks of `N` Argon2 hashes computed in lockstep.
+pub(crate) trait Memory<'a, const N: usize> {
+    /// Compute each Argon2 segment, passing the views into the same segment of each hash's
+    /// memory together.
     ///
     /// By default computation is single threaded. Parallel computation can be enabled with the
     /// `parallel` feature, in which case [rayon] is used to compute as many lanes in parallel as
     /// possible.
     fn for_each_segment<F>(&mut self, lanes: usize, f: F)
     where
-        F: Fn(SegmentView<'_>, usize, usize) + Sync + Send;
+        F: Fn([SegmentView<'_>; N], usize, usize) + Sync + Send;
 }
 
-impl Memory<'_> for &mut [Block] {
+impl<const N: usize> Memory<'_, N> for [&mut [Block]; N] {
     #[cfg(not(feature = "parallel"))]
     fn for_each_segment<F>(&mut self, lanes: usize, f: F)
     where
-        F: Fn(SegmentView<'_>, usize, usize) + Sync + Send,
+        F: Fn([SegmentView<'_>; N], usize, usize) + Sync + Send,
     {
-        let inner = MemoryInner::new(self, lanes);
+        let inners = self
+            .each_mut()
+            .map(|blocks| MemoryInner::new(blocks, lanes));
         for slice in 0..SYNC_POINTS {
             for lane in 0..lanes {
-                // SAFETY: `self` exclusively borrows the blocks, and we sequentially process
-                // slices and segments.
-                let segment = unsafe { SegmentView::new(inner, slice, lane) };
-                f(segment, slice, lane);
+                // SAFETY: `self` exclusively borrows the blocks of each hash, and we sequentially
+                // process slices and segments.
+                let segments = inners.map(|inner| unsafe { SegmentView::new(inner, slice, lane) });
+                f(segments, slice, lane);
             }
         }
     }
@@ -50,15 +53,17 @@ impl Memory<'_> for &mut [Block] {
     #[cfg(feature = "parallel")]
     fn for_each_segment<F>(&mut self, lanes: usize, f: F)
     where
-        F: Fn(SegmentView<'_>, usize, usize) + Sync + Send,
+        F: Fn([SegmentView<'_>; N], usize, usize) + Sync + Send,
     {
-        let inner = MemoryInner::new(self, lanes);
+        let inners = self
+            .each_mut()
+            .map(|blocks| MemoryInner::new(blocks, lanes));
         for slice in 0..SYNC_POINTS {
             (0..lanes).into_par_iter().for_each(|lane| {
-                // SAFETY: `self` exclusively borrows the blocks, we sequentially process slices,
-                // and we create exactly one segment view per lane in a slice.
-                let segment = unsafe { SegmentView::new(inner, slice, lane) };
-                f(segment, slice, lane);
+                // SAFETY: `self` exclusively borrows the blocks of each hash, we sequentially
+                // process slices, and we create exactly one segment view per lane in a slice.
+                let segments = inners.map(|inner| unsafe { SegmentView::new(inner, slice, lane) });
+                f(segments, slice, lane);
             });
         }
     }
@@ -228,6 +228,18 @@ impl Params {
         self.output_len
     }
 
+    /// Set the key identifier.
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    pub(crate) const fn set_keyid(&mut self, keyid: KeyId) {
+        self.keyid = keyid;
+    }
+
+    /// Set the length of the output (in bytes).
+    #[cfg(all(feature = "alloc", feature = "password-hash"))]
+    pub(crate) const fn set_output_len(&mut self, len: usize) {
+        self.output_len = Some(len);
+    }
+
     /// Get the number of lanes.
     #[allow(clippy::cast_possible_truncation)]
     pub(crate) const fn lanes(&self) -> usize {
@@ -615,7 +627,58 @@ impl TryFrom<ParamsBuilder> for Params {
     }
 }
 
+/// Upper bounds on the [`Params`] an [`Argon2`] context accepts when they are selected by its
+/// input, e.g. when verifying a PHC string hash.
+///
+/// Without limits, a hash such as `$argon2id$v=19$m=4294967295,t=4294967295,p=1$...` makes the
+/// verifier allocate 4 TiB of memory and run for an unbounded amount of time. If an attacker can
+/// influence stored hashes, limits should be set to the highest parameters legitimately in use.
+///
+/// By default no limits are imposed beyond those of [`Params`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct VerifierLimits {
+    /// Maximum memory size, expressed in kibibytes.
+    pub max_m_cost: u32,
+
+    /// Maximum number of iterations.
+    pub max_t_cost: u32,
+
+    /// Maximum degree of parallelism.
+    pub max_p_cost: u32,
+}
+
+impl VerifierLimits {
+    /// No limits beyond those of [`Params`].
+    pub const NONE: Self = Self {
+        max_m_cost: Params::MAX_M_COST,
+        max_t_cost: Params::MAX_T_COST,
+        max_p_cost: Params::MAX_P_COST,
+    };
+
+    /// Check the given [`Params`] against these limits.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParamsExceedLimits`] if any of the costs of `params` exceeds its limit.
+    pub const fn check(&self, params: &Params) -> Result<()> {
+        if params.m_cost > self.max_m_cost
+            || params.t_cost > self.max_t_cost
+            || params.p_cost > self.max_p_cost
+        {
+            return Err(Error::ParamsExceedLimits);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for VerifierLimits {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
 #[cfg(all(test, feature = "alloc", feature = "password-hash"))]
+#[allow(clippy::unwrap_used)]
 mod tests {
 
     use super::*;
@@ -660,4 +723,34 @@ mod tests {
         let ret = KeyId::new(&[0u8; Params::MAX_KEYID_LEN + 1]);
         assert_eq!(ret, Err(Error::KeyIdTooLong));
     }
+
+    #[test]
+    fn verifier_limits() {
+        let params = Params::new(2048, 3, 2, None).unwrap();
+        assert_eq!(VerifierLimits::default().check(&params), Ok(()));
+
+        let limits = VerifierLimits {
+            max_m_cost: 2048,
+            max_t_cost: 3,
+            max_p_cost: 2,
+        };
+        assert_eq!(limits.check(&params), Ok(()));
+
+        for limits in [
+            VerifierLimits {
+                max_m_cost: 1024,
+                ..limits
+            },
+            VerifierLimits {
+                max_t_cost: 2,
+                ..limits
+            },
+            VerifierLimits {
+                max_p_cost: 1,
+                ..limits
+            },
+        ] {
+            assert_eq!(limits.check(&params), Err(Error::ParamsExceedLimits));
+        }
+    }
 }
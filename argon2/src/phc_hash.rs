@@ -0,0 +1,238 @@
+//! Argon2 password hashes in PHC string format with long salts.
+
+use crate::{Algorithm, Params, Version};
+use base64ct::{Base64Unpadded as B64, Encoding};
+use core::{fmt, str::FromStr};
+use password_hash::phc::Output;
+
+/// Argon2 password hash in PHC string format:
+///
+/// ```text
+/// $<algorithm>$v=<version>$m=<m_cost>,t=<t_cost>,p=<p_cost>$<salt>$<hash>
+/// ```
+///
+/// Unlike [`PasswordHash`][`password_hash::phc::PasswordHash`], which limits salts to
+/// [`Salt::MAX_LENGTH`][`password_hash::phc::Salt::MAX_LENGTH`] bytes, salts of up to
+/// [`Argon2PasswordHash::MAX_SALT_LEN`] bytes are supported, for interoperability with tools
+/// which generate longer salts.
+///
+/// Hashes can be computed with [`Argon2::hash_password_phc`][`crate::Argon2::hash_password_phc`]
+/// and verified with [`Argon2::verify_password_phc`][`crate::Argon2::verify_password_phc`].
+/// Verifying a PHC string using [`PasswordVerifier<str>`][`crate::PasswordVerifier`] falls back
+/// to this type when the string can't be parsed as a
+/// [`PasswordHash`][`password_hash::phc::PasswordHash`], e.g. because its salt is too long.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Argon2PasswordHash {
+    /// Algorithm
+    algorithm: Algorithm,
+
+    /// Version number
+    version: Version,
+
+    /// Algorithm parameters, including the output length
+    params: Params,
+
+    /// Salt byte array
+    salt: [u8; Self::MAX_SALT_LEN],
+
+    /// Length of salt
+    salt_len: usize,
+
+    /// Hash output
+    hash: Output,
+}
+
+impl Argon2PasswordHash {
+    /// Maximum salt length in bytes.
+    pub const MAX_SALT_LEN: usize = 128;
+
+    /// Parse a PHC string.
+    ///
+    /// # Errors
+    /// Returns an error if the string isn't a well-formed Argon2 PHC string, or if its salt is
+    /// longer than [`Argon2PasswordHash::MAX_SALT_LEN`].
+    pub fn new(s: &str) -> password_hash::Result<Self> {
+        s.parse()
+    }
+
+    /// Create a hash from its components.
+    pub(crate) fn from_parts(
+        algorithm: Algorithm,
+        version: Version,
+        mut params: Params,
+        salt: &[u8],
+        hash: &[u8],
+    ) -> password_hash::Result<Self> {
+        let mut salt_bytes = [0u8; Self::MAX_SALT_LEN];
+        salt_bytes
+            .get_mut(..salt.len())
+            .ok_or(password_hash::Error::SaltInvalid)?
+            .copy_from_slice(salt);
+
+        let hash = Output::new(hash)?;
+        params.set_output_len(hash.len());
+
+        Ok(Self {
+            algorithm,
+            version,
+            params,
+            salt: salt_bytes,
+            salt_len: salt.len(),
+            hash,
+        })
+    }
+
+    /// Get the [`Algorithm`].
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Get the [`Version`].
+    #[must_use]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Get the [`Params`], with the output length set to the length of the hash.
+    #[must_use]
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Get the raw salt bytes.
+    #[must_use]
+    pub fn salt(&self) -> &[u8] {
+        &self.salt[..self.salt_len]
+    }
+
+    /// Get the raw hash output bytes.
+    #[must_use]
+    pub fn hash(&self) -> &[u8] {
+        self.hash.as_ref()
+    }
+}
+
+impl FromStr for Argon2PasswordHash {
+    type Err = password_hash::Error;
+
+    fn from_str(s: &str) -> password_hash::Result<Self> {
+        let mut fields = s.split('$');
+
+        if fields.next() != Some("") {
+            return Err(password_hash::Error::EncodingInvalid);
+        }
+
+        let algorithm =
+            Algorithm::try_from(fields.next().ok_or(password_hash::Error::EncodingInvalid)?)?;
+
+        let mut field = fields.next().ok_or(password_hash::Error::EncodingInvalid)?;
+        let version = match field.strip_prefix("v=") {
+            Some(version) => {
+                field = fields.next().ok_or(password_hash::Error::EncodingInvalid)?;
+                let version = version
+                    .parse::<u32>()
+                    .map_err(|_| password_hash::Error::Version)?;
+                Version::try_from(version)?
+            }
+            None => Version::default(),
+        };
+
+        let params = field.parse::<Params>()?;
+
+        let (Some(salt), Some(hash), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(password_hash::Error::EncodingInvalid);
+        };
+
+        let mut salt_buf = [0u8; Self::MAX_SALT_LEN];
+        let salt =
+            B64::decode(salt, &mut salt_buf).map_err(|_| password_hash::Error::SaltInvalid)?;
+
+        let mut hash_buf = [0u8; Output::MAX_LENGTH];
+        let hash =
+            B64::decode(hash, &mut hash_buf).map_err(|_| password_hash::Error::OutputSize)?;
+
+        Self::from_parts(algorithm, version, params, salt, hash)
+    }
+}
+
+impl fmt::Display for Argon2PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params =
+            password_hash::phc::ParamsString::try_from(&self.params).map_err(|_| fmt::Error)?;
+
+        let mut salt_buf = [0u8; (Self::MAX_SALT_LEN * 4).div_ceil(3)];
+        let salt = B64::encode(self.salt(), &mut salt_buf).map_err(|_| fmt::Error)?;
+
+        let mut hash_buf = [0u8; (Output::MAX_LENGTH * 4).div_ceil(3)];
+        let hash = B64::encode(self.hash(), &mut hash_buf).map_err(|_| fmt::Error)?;
+
+        write!(
+            f,
+            "${}$v={}${}${}${}",
+            self.algorithm,
+            u32::from(self.version),
+            params,
+            salt,
+            hash
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::Argon2PasswordHash;
+    use crate::{Algorithm, Argon2, Params, PasswordVerifier, Version};
+    use alloc::string::ToString;
+    use password_hash::Error;
+
+    /// Example password only: don't use this as a real password!!!
+    const EXAMPLE_PASSWORD: &[u8] = b"hunter42";
+
+    /// Example salt value longer than the PHC string limit. Don't use a static salt value!!!
+    const EXAMPLE_SALT: &[u8] = &[0x5a; 64];
+
+    #[test]
+    fn long_salt_round_trip() {
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(32, 2, 1, None).unwrap(),
+        );
+
+        let hash = argon2
+            .hash_password_phc(EXAMPLE_PASSWORD, EXAMPLE_SALT)
+            .unwrap();
+        assert_eq!(hash.salt(), EXAMPLE_SALT);
+
+        let phc_string = hash.to_string();
+        let parsed = Argon2PasswordHash::new(&phc_string).unwrap();
+        assert_eq!(parsed, hash);
+        assert_eq!(parsed.salt(), EXAMPLE_SALT);
+
+        assert_eq!(
+            Argon2::default().verify_password_phc(EXAMPLE_PASSWORD, &parsed),
+            Ok(())
+        );
+        assert_eq!(
+            Argon2::default().verify_password(EXAMPLE_PASSWORD, phc_string.as_str()),
+            Ok(())
+        );
+        assert_eq!(
+            Argon2::default().verify_password(b"invalid", phc_string.as_str()),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn salt_too_long() {
+        let argon2 = Argon2::from(Params::new(32, 1, 1, None).unwrap());
+        let salt = [0u8; Argon2PasswordHash::MAX_SALT_LEN + 1];
+
+        assert_eq!(
+            argon2.hash_password_phc(EXAMPLE_PASSWORD, &salt),
+            Err(Error::SaltInvalid)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Known-answer self-test.
+
+use crate::{
+    Algorithm, Argon2, AssociatedData, Backend, Block, Error, ParamsBuilder, Result, Version,
+};
+
+/// Expected tags for the test vectors of RFC 9106 Section 5, and their Argon2 version 16
+/// counterparts from the reference implementation.
+const VECTORS: [(Algorithm, Version, [u8; 32]); 6] = [
+    (
+        Algorithm::Argon2d,
+        Version::V0x10,
+        [
+            0x96, 0xa9, 0xd4, 0xe5, 0xa1, 0x73, 0x40, 0x92, 0xc8, 0x5e, 0x29, 0xf4, 0x10, 0xa4,
+            0x59, 0x14, 0xa5, 0xdd, 0x1f, 0x5c, 0xbf, 0x08, 0xb2, 0x67, 0x0d, 0xa6, 0x8a, 0x02,
+            0x85, 0xab, 0xf3, 0x2b,
+        ],
+    ),
+    (
+        Algorithm::Argon2i,
+        Version::V0x10,
+        [
+            0x87, 0xae, 0xed, 0xd6, 0x51, 0x7a, 0xb8, 0x30, 0xcd, 0x97, 0x65, 0xcd, 0x82, 0x31,
+            0xab, 0xb2, 0xe6, 0x47, 0xa5, 0xde, 0xe0, 0x8f, 0x7c, 0x05, 0xe0, 0x2f, 0xcb, 0x76,
+            0x33, 0x35, 0xd0, 0xfd,
+        ],
+    ),
+    (
+        Algorithm::Argon2id,
+        Version::V0x10,
+        [
+            0xb6, 0x46, 0x15, 0xf0, 0x77, 0x89, 0xb6, 0x6b, 0x64, 0x5b, 0x67, 0xee, 0x9e, 0xd3,
+            0xb3, 0x77, 0xae, 0x35, 0x0b, 0x6b, 0xfc, 0xbb, 0x0f, 0xc9, 0x51, 0x41, 0xea, 0x8f,
+            0x32, 0x26, 0x13, 0xc0,
+        ],
+    ),
+    (
+        Algorithm::Argon2d,
+        Version::V0x13,
+        [
+            0x51, 0x2b, 0x39, 0x1b, 0x6f, 0x11, 0x62, 0x97, 0x53, 0x71, 0xd3, 0x09, 0x19, 0x73,
+            0x42, 0x94, 0xf8, 0x68, 0xe3, 0xbe, 0x39, 0x84, 0xf3, 0xc1, 0xa1, 0x3a, 0x4d, 0xb9,
+            0xfa, 0xbe, 0x4a, 0xcb,
+        ],
+    ),
+    (
+        Algorithm::Argon2i,
+        Version::V0x13,
+        [
+            0xc8, 0x14, 0xd9, 0xd1, 0xdc, 0x7f, 0x37, 0xaa, 0x13, 0xf0, 0xd7, 0x7f, 0x24, 0x94,
+            0xbd, 0xa1, 0xc8, 0xde, 0x6b, 0x01, 0x6d, 0xd3, 0x88, 0xd2, 0x99, 0x52, 0xa4, 0xc4,
+            0x67, 0x2b, 0x6c, 0xe8,
+        ],
+    ),
+    (
+        Algorithm::Argon2id,
+        Version::V0x13,
+        [
+            0x0d, 0x64, 0x0d, 0xf5, 0x8d, 0x78, 0x76, 0x6c, 0x08, 0xc0, 0x37, 0xa3, 0x4a, 0x8b,
+            0x53, 0xc9, 0xd0, 0x1e, 0xf0, 0x45, 0x2d, 0x75, 0xb6, 0x5e, 0xb5, 0x25, 0x20, 0xe9,
+            0x6b, 0x01, 0xe6, 0x59,
+        ],
+    ),
+];
+
+/// Run a known-answer test of each Argon2 algorithm and version on every backend available on the
+/// current CPU, returning the [`Backend`] used by default.
+///
+/// The test vectors of RFC 9106 Section 5 are used, which take 32 KiB of memory each. No memory is
+/// allocated: the blocks are stored on the stack. With the `parallel` feature enabled, the lanes
+/// are computed in parallel just as they are for regular hashing.
+///
+/// Services can call this on startup to fail closed in the event an optimized backend
+/// miscomputes on unusual hardware.
+///
+/// # Errors
+/// Returns [`Error::SelfTestFailed`] if any of the computed tags doesn't match the expected one.
+pub fn self_test() -> Result<Backend> {
+    let params = ParamsBuilder::new()
+        .m_cost(32)
+        .t_cost(3)
+        .p_cost(4)
+        .data(AssociatedData::new(&[0x04; 12])?)
+        .output_len(32)
+        .build()?;
+
+    let mut memory = [Block::new(); 32];
+
+    for backend in Backend::ALL.into_iter().filter(|b| b.is_available()) {
+        for (algorithm, version, expected_tag) in VECTORS {
+            let ctx = Argon2::new_with_secret(&[0x03; 8], algorithm, version, params.clone())?
+                .with_backend(backend)?;
+
+            let mut out = [0u8; 32];
+            ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory)?;
+
+            if out != expected_tag {
+                return Err(Error::SelfTestFailed);
+            }
+        }
+    }
+
+    Ok(Backend::detect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::self_test;
+    use crate::Backend;
+
+    #[test]
+    fn self_test_passes() {
+        assert_eq!(self_test(), Ok(Backend::detect()));
+    }
+}
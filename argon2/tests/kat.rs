@@ -11,11 +11,21 @@
 // https://github.com/P-H-C/phc-winner-argon2/blob/master/src/test.c
 
 use argon2::{
-    Algorithm, Argon2, AssociatedData, Error, Params, ParamsBuilder, PasswordHash, PasswordHasher,
-    PasswordVerifier, Version,
+    Algorithm, Argon2, AssociatedData, Backend, Error, Params, ParamsBuilder, PasswordHash,
+    PasswordHasher, PasswordVerifier, Version,
 };
 use hex_literal::hex;
 
+#[cfg(feature = "zeroize")]
+use argon2::{Block, ZeroizingBlocks};
+
+/// Backends available on the current CPU, which the KATs are run on.
+fn available_backends() -> impl Iterator<Item = Backend> {
+    Backend::ALL
+        .into_iter()
+        .filter(|backend| backend.is_available())
+}
+
 /// Params used by the KATs.
 fn example_params() -> Params {
     ParamsBuilder::new()
@@ -60,11 +70,16 @@ fn argon2d_v0x10() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
 
-    assert_eq!(out, expected_tag);
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
 }
 
 /// =======================================
@@ -100,11 +115,16 @@ fn argon2i_v0x10() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
 
-    assert_eq!(out, expected_tag);
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
 }
 
 /// =======================================
@@ -140,11 +160,16 @@ fn argon2id_v0x10() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
 
-    assert_eq!(out, expected_tag);
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
 }
 
 /// =======================================
@@ -193,11 +218,16 @@ fn argon2d_v0x13() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
 
-    assert_eq!(out, expected_tag);
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
 }
 
 /// =======================================
@@ -246,11 +276,16 @@ fn argon2i_v0x13() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
 
-    assert_eq!(out, expected_tag);
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
 }
 
 /// =======================================
@@ -287,11 +322,44 @@ fn argon2id_v0x13() {
         "
     );
 
-    let ctx = Argon2::new_with_secret(&secret, algorithm, version, params).unwrap();
+    for backend in available_backends() {
+        let ctx = Argon2::new_with_secret(&secret, algorithm, version, params.clone())
+            .unwrap()
+            .with_backend(backend)
+            .unwrap();
+        let mut out = [0u8; 32];
+        ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+
+        assert_eq!(out, expected_tag, "{backend} backend");
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroizing_blocks() {
+    let params = example_params();
+    let ctx =
+        Argon2::new_with_secret(&[0x03; 8], Algorithm::Argon2id, Version::V0x13, params).unwrap();
+
+    let mut memory = [Block::new(); 32];
     let mut out = [0u8; 32];
-    ctx.hash_password_into(&password, &salt, &mut out).unwrap();
+    ctx.hash_password_into_with_memory(
+        &[0x01; 32],
+        &[0x02; 16],
+        &mut out,
+        ZeroizingBlocks::new(&mut memory),
+    )
+    .unwrap();
 
-    assert_eq!(out, expected_tag);
+    assert_eq!(
+        out,
+        hex!("0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659")
+    );
+    assert!(
+        memory
+            .iter()
+            .all(|block| block.as_ref().iter().all(|&w| w == 0))
+    );
 }
 
 // =======================================
@@ -362,9 +430,15 @@ fn hashtest(
     let ctx = Argon2::new(algorithm, version, params);
 
     // Test raw hash
-    let mut out = [0u8; 32];
-    ctx.hash_password_into(pwd, salt, &mut out).unwrap();
-    assert_eq!(out, expected_raw_hash);
+    for backend in available_backends() {
+        let mut out = [0u8; 32];
+        ctx.clone()
+            .with_backend(backend)
+            .unwrap()
+            .hash_password_into(pwd, salt, &mut out)
+            .unwrap();
+        assert_eq!(out, expected_raw_hash, "{backend} backend");
+    }
 
     // Test hash encoding
     let phc_hash = ctx.hash_password_with_salt(pwd, salt).unwrap().to_string();
@@ -62,11 +62,31 @@ fn bench_vary_params(c: &mut Criterion) {
     }
 }
 
+fn bench_hash_many(c: &mut Criterion) {
+    let inputs = [(BENCH_PASSWORD, BENCH_SALT); 8];
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default());
+
+    for interleave in [false, true] {
+        let test_name = format!("hash_many 8 hashes interleave={interleave}");
+        c.bench_function(&test_name, |b| {
+            let options = BatchOptions::SEQUENTIAL.with_interleave(interleave);
+            let mut outs = [[0u8; 32]; 8];
+            b.iter(|| {
+                let mut out_refs = outs.each_mut().map(|out| &mut out[..]);
+                argon2
+                    .hash_many(black_box(&inputs), &mut out_refs, options)
+                    .unwrap()
+            })
+        });
+    }
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(300, Output::Flamegraph(None)));
     targets =
     bench_default_params,
     bench_vary_params,
+    bench_hash_many,
 );
 criterion_main!(benches);
@@ -1,13 +1,12 @@
 #![no_main]
 use libfuzzer_sys::arbitrary::{Arbitrary, Result, Unstructured};
 use libfuzzer_sys::fuzz_target;
-use scrypt::password_hash::{
-    Ident, PasswordHash, PasswordHasher, PasswordVerifier, Salt, SaltString,
-};
+use scrypt::password_hash::{CustomizedPasswordHasher, PasswordVerifier};
+use scrypt::phc::{PasswordHash, Salt};
 use scrypt::{scrypt, Scrypt};
 
 #[derive(Debug)]
-pub struct ScryptRandParams(pub scrypt::Params);
+pub struct ScryptRandParams(pub scrypt::Params, pub usize);
 
 impl<'a> Arbitrary<'a> for ScryptRandParams {
     fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
@@ -16,40 +15,32 @@ impl<'a> Arbitrary<'a> for ScryptRandParams {
         let p = u.int_in_range(1..=8)?;
         let len = u.int_in_range(10..=64)?;
 
-        let params = scrypt::Params::new(log_n, r, p, len).unwrap();
-        Ok(Self(params))
+        let params = scrypt::Params::new_with_output_len(log_n, r, p, len).unwrap();
+        Ok(Self(params, len))
     }
 }
 
 fuzz_target!(|data: (&[u8], &[u8], ScryptRandParams)| {
-    let (password, salt, ScryptRandParams(params)) = data;
+    let (password, salt, ScryptRandParams(params, len)) = data;
 
     if password.len() > 64 {
         return;
     }
 
-    if salt.len() < Salt::MIN_LENGTH || salt.len() > (6 * Salt::MAX_LENGTH) / 8 {
+    if Salt::new(salt).is_err() {
         return;
     }
 
     // Check direct hashing
-    let mut result = [0u8; 64];
+    let mut result = vec![0u8; len];
     scrypt(password, salt, &params, &mut result).unwrap();
 
     // Check PHC hashing
-    let salt_string = SaltString::encode_b64(salt).unwrap();
-    let phc_hash = Scrypt
-        .hash_password_customized(
-            password,
-            Some(Ident::new_unwrap("scrypt")),
-            None,
-            params,
-            &salt_string,
-        )
-        .unwrap()
-        .to_string();
+    let phc_hash: PasswordHash = Scrypt::new()
+        .hash_password_customized(password, salt, Some("scrypt"), None, params)
+        .unwrap();
 
     // Check PHC verification
-    let hash = PasswordHash::new(&phc_hash).unwrap();
-    Scrypt.verify_password(password, &hash).unwrap();
+    let hash = PasswordHash::new(&phc_hash.to_string()).unwrap();
+    Scrypt::new().verify_password(password, &hash).unwrap();
 });
@@ -0,0 +1,36 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use scrypt::fuzzing::{scrypt_block_mix, scrypt_block_mix_soft, scrypt_block_mix_x2};
+
+fuzz_target!(|data: &[u8]| {
+    // Split the input into two lanes of the same whole number of 128-byte blocks
+    let len = (data.len() / 256) * 128;
+    if len == 0 {
+        return;
+    }
+
+    let mut input0 = data[..len].to_vec();
+    let mut input1 = data[len..2 * len].to_vec();
+
+    let mut expected0 = vec![0u8; len];
+    let mut expected1 = vec![0u8; len];
+    scrypt_block_mix_soft(&input0, &mut expected0);
+    scrypt_block_mix_soft(&input1, &mut expected1);
+
+    // Check the backend selected for the target against the portable one
+    let mut output0 = vec![0u8; len];
+    scrypt_block_mix(&mut input0, &mut output0);
+    assert_eq!(
+        input0,
+        &data[..len],
+        "shuffle_out is not the inverse of shuffle_in"
+    );
+    assert_eq!(output0, expected0);
+
+    // Check the two-lane backend against the portable one
+    let mut output0 = vec![0u8; len];
+    let mut output1 = vec![0u8; len];
+    scrypt_block_mix_x2(&mut input0, &mut output0, &mut input1, &mut output1);
+    assert_eq!(output0, expected0);
+    assert_eq!(output1, expected1);
+});
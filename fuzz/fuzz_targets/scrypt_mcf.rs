@@ -0,0 +1,34 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use scrypt::fuzzing::{mcf_decode_params_and_salt, mcf_encode_params};
+use scrypt::mcf::PasswordHashRef;
+use scrypt::password_hash::PasswordVerifier;
+use scrypt::{Scrypt, VerifierLimits};
+
+/// Limits keeping the cost of verifying arbitrary hashes low.
+const LIMITS: VerifierLimits = VerifierLimits {
+    max_memory: 1 << 20,
+    max_log_n: 10,
+    max_p: 4,
+};
+
+fuzz_target!(|data: (&str, &[u8])| {
+    let (hash, password) = data;
+
+    // Check decoding the `$7$` params and salt field
+    let field = hash.split('$').nth(2).unwrap_or(hash);
+    if let Ok((params, salt)) = mcf_decode_params_and_salt(field) {
+        // Params are encoded with a fixed width, so re-encoding them gives the same string
+        let mut buf = [0u8; 11];
+        let encoded = mcf_encode_params(params, &mut buf).unwrap();
+        assert_eq!(&field.as_bytes()[..encoded.len()], encoded.as_bytes());
+        assert_eq!(&field.as_bytes()[encoded.len()..], salt);
+    }
+
+    // Check verification doesn't panic
+    if let Ok(hash) = PasswordHashRef::new(hash) {
+        let _ = Scrypt::new()
+            .with_verifier_limits(LIMITS)
+            .verify_password(password, hash);
+    }
+});
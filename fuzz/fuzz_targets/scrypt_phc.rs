@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use scrypt::password_hash::phc::ParamsString;
+use scrypt::password_hash::PasswordVerifier;
+use scrypt::phc::PasswordHash;
+use scrypt::{Params, Scrypt, VerifierLimits};
+use std::convert::TryFrom;
+
+/// Limits keeping the cost of verifying arbitrary hashes low.
+const LIMITS: VerifierLimits = VerifierLimits {
+    max_memory: 1 << 20,
+    max_log_n: 10,
+    max_p: 4,
+};
+
+fuzz_target!(|data: (&str, &[u8])| {
+    let (hash, password) = data;
+
+    let hash = match PasswordHash::new(hash) {
+        Ok(hash) => hash,
+        Err(_) => return,
+    };
+
+    // Check decoding params, and that they round trip through a `ParamsString`
+    if let Ok(params) = Params::try_from(&hash) {
+        let params_string = ParamsString::try_from(&params).unwrap();
+        let decoded = Params::try_from(&params_string).unwrap();
+        assert_eq!(decoded.log_n(), params.log_n());
+        assert_eq!(decoded.r(), params.r());
+        assert_eq!(decoded.p(), params.p());
+    }
+
+    // Check verification doesn't panic
+    let _ = Scrypt::new()
+        .with_verifier_limits(LIMITS)
+        .verify_password(password, &hash);
+});
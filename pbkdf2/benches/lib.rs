@@ -54,3 +54,25 @@ pub fn pbkdf2_hmac_sha512_210000_20(bh: &mut Bencher) {
         test::black_box(&buf);
     });
 }
+
+#[bench]
+pub fn pbkdf2_hmac_sha256_16384_128(bh: &mut Bencher) {
+    let password = b"my secure password";
+    let salt = b"salty salt";
+    let mut buf = [0u8; 128];
+    bh.iter(|| {
+        pbkdf2::pbkdf2_hmac_sha256(password, salt, 16_384, &mut buf);
+        test::black_box(&buf);
+    });
+}
+
+#[bench]
+pub fn pbkdf2_hmac_sha512_16384_256(bh: &mut Bencher) {
+    let password = b"my secure password";
+    let salt = b"salty salt";
+    let mut buf = [0u8; 256];
+    bh.iter(|| {
+        pbkdf2::pbkdf2_hmac_sha512(password, salt, 16_384, &mut buf);
+        test::black_box(&buf);
+    });
+}
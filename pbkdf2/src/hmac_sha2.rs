@@ -0,0 +1,275 @@
+//! Optimized PBKDF2-HMAC-SHA-256 and PBKDF2-HMAC-SHA-512.
+//!
+//! They are exposed as the [`pbkdf2_hmac_sha256`] and [`pbkdf2_hmac_sha512`] functions, which
+//! are also used by [`Pbkdf2`][`crate::Pbkdf2`].
+//!
+//! Each PBKDF2 round computes an HMAC of the previous round's output, which always fits in a
+//! single block once padded. Instead of going through the generic [`Hmac`] API, the states after
+//! absorbing the ipad and opad blocks are computed once, and each round is then exactly two calls
+//! to the SHA-2 compression function, which uses the SHA-NI or ARM SHA extensions when they are
+//! available on the running CPU.
+//!
+//! Up to [`LANES`] output blocks are computed in lockstep, so the compressions of independent
+//! blocks can overlap in the CPU pipeline. With the `parallel` feature enabled, groups of output
+//! blocks are additionally computed on different threads using [rayon](https://docs.rs/rayon).
+
+use crate::xor;
+use digest::{Digest, FixedOutput, KeyInit, Update, block_api::EagerHash};
+use hmac::Hmac;
+use sha2::{
+    Sha256, Sha512,
+    block_api::{compress256, compress512},
+};
+
+/// Maximum number of output blocks computed in lockstep.
+const LANES: usize = 4;
+
+/// Optimized implementation of [`pbkdf2_hmac::<Sha256>`][`crate::pbkdf2_hmac`].
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::pbkdf2_hmac_sha256;
+///
+/// let mut buf = [0u8; 20];
+/// pbkdf2_hmac_sha256(b"password", b"salt", 600_000, &mut buf);
+/// assert_eq!(buf, hex!("669cfe52482116fda1aa2cbe409b2f56c8e45637"));
+/// ```
+///
+/// # Panics
+/// Panics if `res` is longer than `(2^32 - 1) * 32` bytes, the maximum output length of
+/// PBKDF2.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], rounds: u32, res: &mut [u8]) {
+    pbkdf2_hmac_sha2::<Sha256State>(password, salt, rounds, res);
+}
+
+/// Optimized implementation of [`pbkdf2_hmac::<Sha512>`][`crate::pbkdf2_hmac`].
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::pbkdf2_hmac_sha512;
+///
+/// let mut buf = [0u8; 20];
+/// pbkdf2_hmac_sha512(b"password", b"salt", 600_000, &mut buf);
+/// assert_eq!(buf, hex!("8abe2c1b191373f7863d1ec622f713153ef20ece"));
+/// ```
+///
+/// # Panics
+/// Panics if `res` is longer than `(2^32 - 1) * 64` bytes, the maximum output length of
+/// PBKDF2.
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], rounds: u32, res: &mut [u8]) {
+    pbkdf2_hmac_sha2::<Sha512State>(password, salt, rounds, res);
+}
+
+/// SHA-2 hash function with direct access to its state and compression function.
+trait Sha2State: Copy + Send + Sync {
+    /// Hash function.
+    type Digest: EagerHash<Core: Sync>;
+
+    /// Block, as taken by the compression function.
+    type Block: Copy + AsRef<[u8]> + AsMut<[u8]> + Send + Sync;
+
+    /// Initial hash state.
+    const IV: Self;
+
+    /// Zeroed block.
+    const ZERO_BLOCK: Self::Block;
+
+    /// Size of the hash output in bytes.
+    const OUTPUT_LEN: usize;
+
+    /// Compress a single block into the state.
+    fn compress(&mut self, block: &Self::Block);
+
+    /// Write the state as the big-endian hash output to the start of `out`.
+    fn write(&self, out: &mut [u8]);
+}
+
+/// SHA-256 hash state.
+#[derive(Clone, Copy)]
+struct Sha256State([u32; 8]);
+
+impl Sha2State for Sha256State {
+    type Digest = Sha256;
+    type Block = [u8; 64];
+
+    const IV: Self = Self([
+        0x6a09_e667,
+        0xbb67_ae85,
+        0x3c6e_f372,
+        0xa54f_f53a,
+        0x510e_527f,
+        0x9b05_688c,
+        0x1f83_d9ab,
+        0x5be0_cd19,
+    ]);
+    const ZERO_BLOCK: [u8; 64] = [0; 64];
+    const OUTPUT_LEN: usize = 32;
+
+    #[inline(always)]
+    fn compress(&mut self, block: &[u8; 64]) {
+        compress256(&mut self.0, core::slice::from_ref(block));
+    }
+
+    #[inline(always)]
+    fn write(&self, out: &mut [u8]) {
+        for (chunk, word) in out.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+/// SHA-512 hash state.
+#[derive(Clone, Copy)]
+struct Sha512State([u64; 8]);
+
+impl Sha2State for Sha512State {
+    type Digest = Sha512;
+    type Block = [u8; 128];
+
+    const IV: Self = Self([
+        0x6a09_e667_f3bc_c908,
+        0xbb67_ae85_84ca_a73b,
+        0x3c6e_f372_fe94_f82b,
+        0xa54f_f53a_5f1d_36f1,
+        0x510e_527f_ade6_82d1,
+        0x9b05_688c_2b3e_6c1f,
+        0x1f83_d9ab_fb41_bd6b,
+        0x5be0_cd19_137e_2179,
+    ]);
+    const ZERO_BLOCK: [u8; 128] = [0; 128];
+    const OUTPUT_LEN: usize = 64;
+
+    #[inline(always)]
+    fn compress(&mut self, block: &[u8; 128]) {
+        compress512(&mut self.0, core::slice::from_ref(block));
+    }
+
+    #[inline(always)]
+    fn write(&self, out: &mut [u8]) {
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+/// Working state of the computation of one output block.
+#[derive(Clone, Copy)]
+struct Lane<S: Sha2State> {
+    /// Padded input of the inner hash, starting with the previous round's output.
+    inner: S::Block,
+
+    /// Padded input of the outer hash, starting with the inner hash.
+    outer: S::Block,
+
+    /// XOR of the outputs of all rounds so far.
+    acc: S::Block,
+}
+
+/// Compute PBKDF2-HMAC with the SHA-2 hash function corresponding to `S`.
+fn pbkdf2_hmac_sha2<S: Sha2State>(password: &[u8], salt: &[u8], rounds: u32, res: &mut [u8]) {
+    let n = S::OUTPUT_LEN;
+    let prf = Hmac::<S::Digest>::new_from_slice(password)
+        .expect("HMAC can be initialized with any key length");
+
+    // States after absorbing the ipad and opad blocks
+    let mut key = S::ZERO_BLOCK;
+    if password.len() > key.as_ref().len() {
+        S::Digest::digest(password)
+            .iter()
+            .zip(key.as_mut())
+            .for_each(|(b, k)| *k = *b);
+    } else {
+        key.as_mut()[..password.len()].copy_from_slice(password);
+    }
+
+    let mut inner_state = S::IV;
+    let mut outer_state = S::IV;
+    let mut pad = key;
+    pad.as_mut().iter_mut().for_each(|b| *b ^= 0x36);
+    inner_state.compress(&pad);
+    pad = key;
+    pad.as_mut().iter_mut().for_each(|b| *b ^= 0x5c);
+    outer_state.compress(&pad);
+
+    // Both hashes are computed over a block followed by `n` bytes, so they share padding
+    let mut padded = S::ZERO_BLOCK;
+    let block_len = padded.as_ref().len();
+    padded.as_mut()[n] = 0x80;
+    let bit_len = 8 * (block_len + n) as u128;
+    padded.as_mut()[block_len - 16..].copy_from_slice(&bit_len.to_be_bytes());
+
+    let lane = Lane::<S> {
+        inner: padded,
+        outer: padded,
+        acc: S::ZERO_BLOCK,
+    };
+
+    // Compute the output blocks of a group of at most `LANES` blocks in lockstep
+    let compute_group = |group_index: usize, group: &mut [u8], lanes_per_group: usize| {
+        let mut lanes = [lane; LANES];
+        let lanes = &mut lanes[..group.len().div_ceil(n)];
+
+        // The first round hashes the salt and block index
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            // PBKDF2 numbers the output blocks from 1 using 32-bit big-endian integers
+            let block_number = u32::try_from(group_index * lanes_per_group + i + 1)
+                .expect("output is at most 2^32 - 1 blocks long");
+
+            let mut prfc = prf.clone();
+            prfc.update(salt);
+            prfc.update(&block_number.to_be_bytes());
+            let u = prfc.finalize_fixed();
+
+            lane.inner.as_mut()[..n].copy_from_slice(&u);
+            lane.acc.as_mut()[..n].copy_from_slice(&u);
+        }
+
+        for _ in 1..rounds {
+            for lane in lanes.iter_mut() {
+                let mut state = inner_state;
+                state.compress(&lane.inner);
+                state.write(lane.outer.as_mut());
+            }
+
+            for lane in lanes.iter_mut() {
+                let mut state = outer_state;
+                state.compress(&lane.outer);
+                state.write(lane.inner.as_mut());
+                xor(&mut lane.acc.as_mut()[..n], lane.inner.as_ref());
+            }
+        }
+
+        for (chunk, lane) in group.chunks_mut(n).zip(lanes.iter()) {
+            chunk.copy_from_slice(&lane.acc.as_ref()[..chunk.len()]);
+        }
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        for (group_index, group) in res.chunks_mut(n * LANES).enumerate() {
+            compute_group(group_index, group, LANES);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::{
+            current_num_threads,
+            iter::{IndexedParallelIterator, ParallelIterator},
+            slice::ParallelSliceMut,
+        };
+
+        // Spread the blocks over the threads before computing several of them in lockstep
+        let lanes_per_group = res
+            .len()
+            .div_ceil(n)
+            .div_ceil(current_num_threads())
+            .clamp(1, LANES);
+
+        res.par_chunks_mut(n * lanes_per_group)
+            .enumerate()
+            .for_each(|(group_index, group)| {
+                compute_group(group_index, group, lanes_per_group);
+            });
+    }
+}
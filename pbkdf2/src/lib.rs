@@ -80,6 +80,17 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Parallelism
+//!
+//! With the `parallel` feature enabled, [`pbkdf2_hmac_sha256`] and [`pbkdf2_hmac_sha512`], and
+//! with them [`Pbkdf2`], compute their output blocks on multiple threads using
+//! [rayon](https://docs.rs/rayon).
+//!
+//! The generic [`pbkdf2`], [`pbkdf2_hmac`] and [`pbkdf2_array`] functions always compute their
+//! output blocks one after the other, as doing so in parallel would require the PRF to be `Sync`
+//! only when the feature is enabled. The feature instead adds `pbkdf2_par`, a variant of
+//! [`pbkdf2`] for `Sync` PRFs which computes the output blocks on multiple threads.
 
 #[cfg(feature = "mcf")]
 pub mod mcf;
@@ -89,10 +100,16 @@ pub mod phc;
 #[cfg(feature = "sha2")]
 mod algorithm;
 #[cfg(feature = "sha2")]
+mod hmac_sha2;
+#[cfg(feature = "sha2")]
 mod params;
 
 #[cfg(feature = "sha2")]
-pub use crate::{algorithm::Algorithm, params::Params};
+pub use crate::{
+    algorithm::Algorithm,
+    hmac_sha2::{pbkdf2_hmac_sha256, pbkdf2_hmac_sha512},
+    params::Params,
+};
 #[cfg(feature = "hmac")]
 pub use hmac;
 #[cfg(any(feature = "mcf", feature = "phc"))]
@@ -145,6 +162,10 @@ where
 
 /// Generic implementation of PBKDF2 algorithm which accepts an arbitrary keyed PRF.
 ///
+/// The output blocks are computed one after the other, even when the `parallel` feature is
+/// enabled: see `pbkdf2_par` for a variant which computes them on multiple threads, and
+/// [`pbkdf2_hmac_sha256`] and [`pbkdf2_hmac_sha512`] for optimized implementations.
+///
 #[cfg_attr(feature = "sha2", doc = "```")]
 #[cfg_attr(not(feature = "sha2"), doc = "```ignore")]
 /// use hex_literal::hex;
@@ -179,6 +200,51 @@ where
     Ok(())
 }
 
+/// A variant of the [`pbkdf2`] function which computes the output blocks on multiple threads using
+/// [rayon](https://docs.rs/rayon).
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::{pbkdf2_par, hmac::Hmac, sha2::Sha256};
+///
+/// let mut buf = [0u8; 64];
+/// pbkdf2_par::<Hmac<Sha256>>(b"password", b"salt", 600_000, &mut buf)
+///     .expect("HMAC can be initialized with any key length");
+/// assert_eq!(buf[..20], hex!("669cfe52482116fda1aa2cbe409b2f56c8e45637"));
+/// ```
+///
+/// # Errors
+/// Returns `InvalidLength` if the length of `password` is unsupported by `PRF`.
+///
+/// # Panics
+/// Panics if `res` is longer than `2^32 - 1` output blocks of `PRF`, the maximum output length of
+/// PBKDF2.
+#[cfg(feature = "parallel")]
+pub fn pbkdf2_par<PRF>(
+    password: &[u8],
+    salt: &[u8],
+    rounds: u32,
+    res: &mut [u8],
+) -> Result<(), InvalidLength>
+where
+    PRF: KeyInit + Update + FixedOutput + Clone + Sync,
+{
+    use rayon::{
+        iter::{IndexedParallelIterator, ParallelIterator},
+        slice::ParallelSliceMut,
+    };
+
+    let n = PRF::OutputSize::to_usize();
+    let prf = PRF::new_from_slice(password)?;
+
+    res.par_chunks_mut(n).enumerate().for_each(|(i, chunk)| {
+        let i = u32::try_from(i).expect("output is at most 2^32 - 1 blocks long");
+        pbkdf2_body(i, chunk, &prf, salt, rounds);
+    });
+
+    Ok(())
+}
+
 /// A variant of the [`pbkdf2`] function which returns an array instead of filling an input slice.
 ///
 #[cfg_attr(feature = "sha2", doc = "```")]
@@ -208,7 +274,11 @@ where
 
 /// A variant of the [`pbkdf2`] function which uses HMAC for PRF.
 ///
-/// It's generic over (eager) hash functions.
+/// It's generic over (eager) hash functions. When the `sha2` feature is enabled, the
+/// [`pbkdf2_hmac_sha256`] and [`pbkdf2_hmac_sha512`] functions provide optimized implementations
+/// for SHA-256 and SHA-512, which use the SHA extensions of x86 and ARM CPUs when available and
+/// compute several output blocks at once. With the `parallel` feature enabled, their output blocks
+/// are also computed on multiple threads.
 ///
 #[cfg_attr(feature = "sha2", doc = "```")]
 #[cfg_attr(not(feature = "sha2"), doc = "```ignore")]
@@ -276,9 +346,9 @@ pub fn pbkdf2_hmac_with_params(
 ) {
     let f = match algorithm {
         #[cfg(feature = "sha2")]
-        Algorithm::Pbkdf2Sha256 => pbkdf2_hmac::<sha2::Sha256>,
+        Algorithm::Pbkdf2Sha256 => pbkdf2_hmac_sha256,
         #[cfg(feature = "sha2")]
-        Algorithm::Pbkdf2Sha512 => pbkdf2_hmac::<sha2::Sha512>,
+        Algorithm::Pbkdf2Sha512 => pbkdf2_hmac_sha512,
     };
 
     f(password, salt, params.rounds(), out);
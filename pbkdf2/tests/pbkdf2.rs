@@ -22,8 +22,34 @@ macro_rules! test {
 
             let hash = pbkdf2::pbkdf2_hmac_array::<$hash, N>($password, $salt, $rounds);
             assert_eq!(hash[..], EXPECTED_HASH[..]);
+
+            #[cfg(feature = "parallel")]
+            {
+                let mut hash = [0u8; N];
+                pbkdf2::pbkdf2_par::<hmac::Hmac<$hash>>($password, $salt, $rounds, &mut hash)
+                    .unwrap();
+                assert_eq!(hash[..], EXPECTED_HASH[..]);
+            }
         })*
     };
+    // Also check the optimized implementation for `$hash`
+    (
+        $hash:ty => $optimized:path;
+        $($password:expr, $salt:expr, $rounds:expr, $($expected_hash:literal)*;)*
+    ) => {
+        test!($hash; $($password, $salt, $rounds, $($expected_hash)*;)*);
+
+        $(
+            #[cfg(feature = "sha2")]
+            {
+                const EXPECTED_HASH: &[u8] = &hex_literal::hex!($($expected_hash)*);
+
+                let mut hash = [0u8; EXPECTED_HASH.len()];
+                $optimized($password, $salt, $rounds, &mut hash);
+                assert_eq!(hash[..], EXPECTED_HASH[..]);
+            }
+        )*
+    };
 }
 
 /// Test vectors from RFC 6070: <https://www.rfc-editor.org/rfc/rfc6070>
@@ -84,6 +110,56 @@ fn pbkdf2_streebog() {
     );
 }
 
+/// PBKDF2-HMAC-SHA256 test vectors from RFC 7914: <https://www.rfc-editor.org/rfc/rfc7914#section-11>
+#[test]
+fn pbkdf2_rfc7914() {
+    test!(
+        Sha256 => pbkdf2::pbkdf2_hmac_sha256;
+        b"passwd", b"salt", 1,
+            "55ac046e56e3089fec1691c22544b605"
+            "f94185216dde0465e68b9d57c20dacbc"
+            "49ca9cccf179b645991664b39d77ef31"
+            "7c71b845b1e30bd509112041d3a19783";
+        b"Password", b"NaCl", 80_000,
+            "4ddcd8f60b98be21830cee5ef22701f9"
+            "641a4418d04c0414aeff08876b34ab56"
+            "a1d425a1225833549adb841b51c9b317"
+            "6a272bdebba1d078478f62b397f33c8d";
+    );
+}
+
+/// The optimized implementations of PBKDF2-HMAC-SHA-256 and PBKDF2-HMAC-SHA-512 should match
+/// the generic implementation for any output and password length.
+#[cfg(feature = "sha2")]
+#[test]
+fn pbkdf2_hmac_sha2_matches_generic() {
+    fn check<D: hmac::EagerHash>(optimized: fn(&[u8], &[u8], u32, &mut [u8])) {
+        let long_password = [0x42u8; 200];
+
+        for password in [&b""[..], b"password", &long_password] {
+            for len in [1, 20, 32, 33, 64, 65, 128, 255, 256, 257, 300] {
+                for rounds in [1, 2, 3] {
+                    let mut expected = [0u8; 300];
+                    pbkdf2::pbkdf2::<hmac::Hmac<D>>(
+                        password,
+                        b"salt",
+                        rounds,
+                        &mut expected[..len],
+                    )
+                    .unwrap();
+
+                    let mut actual = [0u8; 300];
+                    optimized(password, b"salt", rounds, &mut actual[..len]);
+                    assert_eq!(actual[..len], expected[..len]);
+                }
+            }
+        }
+    }
+
+    check::<Sha256>(pbkdf2::pbkdf2_hmac_sha256);
+    check::<Sha512>(pbkdf2::pbkdf2_hmac_sha512);
+}
+
 /// Test vector from STB 34.101.45-2013 (page 33):
 /// <https://apmi.bsu.by/assets/files/std/bign-spec294.pdf>
 #[test]
@@ -99,7 +175,7 @@ fn pbkdf2_belt() {
 #[test]
 fn pbkdf2_algorithm_defaults_use_matching_rounds_sha_256() {
     test!(
-        Sha256;
+        Sha256 => pbkdf2::pbkdf2_hmac_sha256;
         b"password", b"salt", 1, "120fb6cffcf8b32c43e7225256c4f837a86548c9";
         b"password", b"salt", 2, "ae4d0c95af6b46d32d0adff928f06dd02a303f8e";
         b"password", b"salt", 4096, "c5e478d59288c841aa530db6845c4c8d962893a0";
@@ -114,7 +190,7 @@ fn pbkdf2_algorithm_defaults_use_matching_rounds_sha_256() {
 #[test]
 fn pbkdf2_algorithm_defaults_use_matching_rounds_sha_512() {
     test!(
-        Sha512;
+        Sha512 => pbkdf2::pbkdf2_hmac_sha512;
         b"password", b"salt", 1, "867f70cf1ade02cff3752599a3a53dc4af34c7a6";
         b"password", b"salt", 2, "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e";
         b"password", b"salt", 4096, "d197b1b33db0143e018b12f3d1d1479e6cdebdcc";
@@ -2,19 +2,77 @@ cfg_if::cfg_if! {
     if #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))] {
         mod pivot;
         mod simd128;
-        pub(crate) use simd128::{scrypt_block_mix, shuffle_in, shuffle_out};
+        pub(crate) use {
+            pivot::{shuffle_in, shuffle_out},
+            simd128::scrypt_block_mix,
+        };
 
-        #[cfg(test)]
+        #[cfg(any(test, fuzzing))]
         #[path = "block_mix/soft.rs"]
-        mod soft_test;
+        pub(crate) mod soft_test;
     } else if #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2"))] {
+        mod avx2;
         mod pivot;
         mod sse2;
-        pub(crate) use sse2::{scrypt_block_mix, shuffle_in, shuffle_out};
+        pub(crate) use {
+            pivot::{shuffle_in, shuffle_out},
+            sse2::scrypt_block_mix,
+        };
 
-        #[cfg(test)]
+        cpufeatures::new!(avx2_cpuid, "avx2");
+
+        /// Is [`scrypt_block_mix_x2`] faster than computing `BlockMix` for each lane in turn?
+        #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+        pub(crate) fn x2_available() -> bool {
+            avx2_cpuid::get()
+        }
+
+        /// Execute the `BlockMix` operation on two lanes at once, using AVX2 if available.
+        ///
+        /// # Panics
+        /// Panics if the inputs and outputs don't all have the same length, or if that length
+        /// isn't a non-zero multiple of 128.
+        #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+        pub(crate) fn scrypt_block_mix_x2(
+            input0: &[u8],
+            output0: &mut [u8],
+            input1: &[u8],
+            output1: &mut [u8],
+        ) {
+            let len = input0.len();
+            assert!(
+                len != 0 && len % 128 == 0,
+                "input must be a non-zero multiple of 128"
+            );
+            assert!(
+                input1.len() == len && output0.len() == len && output1.len() == len,
+                "inputs and outputs must be the same length"
+            );
+
+            if avx2_cpuid::get() {
+                // SAFETY: checked that AVX2 was detected, and asserted above that the inputs and
+                // outputs all have the same length, which is a multiple of 128.
+                unsafe { avx2::scrypt_block_mix_x2(input0, output0, input1, output1) }
+            } else {
+                scrypt_block_mix(input0, output0);
+                scrypt_block_mix(input1, output1);
+            }
+        }
+
+        #[cfg(any(test, fuzzing))]
         #[path = "block_mix/soft.rs"]
-        mod soft_test;
+        pub(crate) mod soft_test;
+    } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon"))] {
+        mod neon;
+        mod pivot;
+        pub(crate) use {
+            pivot::{shuffle_in, shuffle_out},
+            neon::scrypt_block_mix,
+        };
+
+        #[cfg(any(test, fuzzing))]
+        #[path = "block_mix/soft.rs"]
+        pub(crate) mod soft_test;
     } else {
         mod soft;
         pub(crate) use soft::scrypt_block_mix;
@@ -22,11 +80,37 @@ cfg_if::cfg_if! {
         pub(crate) fn shuffle_in(_input: &mut [u8]) {}
         pub(crate) fn shuffle_out(_input: &mut [u8]) {}
 
-        #[cfg(test)]
-        use soft as soft_test;
+        #[cfg(any(test, fuzzing))]
+        pub(crate) use soft as soft_test;
     }
 }
 
+/// Is [`scrypt_block_mix_x2`] faster than computing `BlockMix` for each lane in turn?
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+)))]
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub(crate) fn x2_available() -> bool {
+    false
+}
+
+/// Execute the `BlockMix` operation on two lanes at once.
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+)))]
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub(crate) fn scrypt_block_mix_x2(
+    input0: &[u8],
+    output0: &mut [u8],
+    input1: &[u8],
+    output1: &mut [u8],
+) {
+    scrypt_block_mix(input0, output0);
+    scrypt_block_mix(input1, output1);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +151,63 @@ mod tests {
                 .for_each(|(a, b)| *a = a.wrapping_add(*b));
         }
     }
+
+    #[test]
+    fn test_scrypt_block_mix_x2_against_soft() {
+        let mut input0: [u8; 256] = core::array::from_fn(|i| u8::try_from(i % 256).unwrap());
+        let mut input1: [u8; 256] = core::array::from_fn(|i| u8::try_from(255 - i).unwrap());
+
+        for _round in 0..10 {
+            let mut output0 = [0u8; 256];
+            let mut output1 = [0u8; 256];
+
+            let mut expected0 = [0u8; 256];
+            let mut expected1 = [0u8; 256];
+
+            #[allow(unused_qualifications, reason = "might be imported for `soft`")]
+            soft_test::scrypt_block_mix(&input0, &mut expected0);
+            #[allow(unused_qualifications, reason = "might be imported for `soft`")]
+            soft_test::scrypt_block_mix(&input1, &mut expected1);
+
+            shuffle_in(&mut input0);
+            shuffle_in(&mut input1);
+            scrypt_block_mix_x2(&input0, &mut output0, &input1, &mut output1);
+            shuffle_out(&mut input0);
+            shuffle_out(&mut input1);
+
+            shuffle_out(&mut output0);
+            shuffle_out(&mut output1);
+            assert_eq!(
+                output0, expected0,
+                "output0 != expected0, scrypt_block_mix_x2 is not correct?"
+            );
+            assert_eq!(
+                output1, expected1,
+                "output1 != expected1, scrypt_block_mix_x2 is not correct?"
+            );
+
+            input0
+                .iter_mut()
+                .zip(output1.iter())
+                .for_each(|(a, b)| *a = a.wrapping_add(*b));
+            input1
+                .iter_mut()
+                .zip(output0.iter())
+                .for_each(|(a, b)| *a = a.wrapping_sub(*b));
+        }
+    }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    ))]
+    #[test]
+    #[should_panic(expected = "inputs and outputs must be the same length")]
+    fn test_scrypt_block_mix_x2_length_mismatch() {
+        let input0 = [0u8; 256];
+        let input1 = [0u8; 128];
+        let mut output0 = [0u8; 256];
+        let mut output1 = [0u8; 256];
+        scrypt_block_mix_x2(&input0, &mut output0, &input1, &mut output1);
+    }
 }
@@ -0,0 +1,103 @@
+//! AVX2 implementation computing `BlockMix` for two independent lanes at once.
+//!
+//! Uses the same diagonal data layout as the SSE2 implementation, with the first lane in the low
+//! 128 bits and the second lane in the high 128 bits of each register.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Execute the `BlockMix` operation on two lanes at once.
+///
+/// # Safety
+/// The CPU must support AVX2. The inputs must be the same length, which must be a non-zero
+/// multiple of 128, and the outputs must be the same length as the inputs.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn scrypt_block_mix_x2(
+    input0: &[u8],
+    output0: &mut [u8],
+    input1: &[u8],
+    output1: &mut [u8],
+) {
+    macro_rules! mm256_rol_epi32x {
+        ($w:expr, $amt:literal) => {{
+            let w = $w;
+            _mm256_or_si256(_mm256_slli_epi32(w, $amt), _mm256_srli_epi32(w, 32 - $amt))
+        }};
+    }
+
+    let len = input0.len();
+    debug_assert_eq!(len % 128, 0, "input must be a multiple of 128");
+    debug_assert!(
+        input1.len() == len && output0.len() == len && output1.len() == len,
+        "inputs and outputs must be the same length"
+    );
+
+    let last0 = &input0[len - 64..];
+    let last1 = &input1[len - 64..];
+
+    // SAFETY: the caller guarantees that AVX2 is available, and `last0` and `last1` are 64 bytes
+    // long, so each unaligned 16-byte load at offsets 0, 16, 32 and 48 is in bounds.
+    let mut a = unsafe { _mm256_loadu2_m128i(last1.as_ptr().cast(), last0.as_ptr().cast()) };
+    // SAFETY: see above.
+    let mut b = unsafe {
+        _mm256_loadu2_m128i(last1.as_ptr().add(16).cast(), last0.as_ptr().add(16).cast())
+    };
+    // SAFETY: see above.
+    let mut c = unsafe {
+        _mm256_loadu2_m128i(last1.as_ptr().add(32).cast(), last0.as_ptr().add(32).cast())
+    };
+    // SAFETY: see above.
+    let mut d = unsafe {
+        _mm256_loadu2_m128i(last1.as_ptr().add(48).cast(), last0.as_ptr().add(48).cast())
+    };
+
+    for (i, (chunk0, chunk1)) in input0.chunks(64).zip(input1.chunks(64)).enumerate() {
+        let pos = if i % 2 == 0 {
+            (i / 2) * 64
+        } else {
+            (i / 2) * 64 + len / 2
+        };
+
+        // SAFETY: the caller guarantees that AVX2 is available, and that all slices have the same
+        // length, a multiple of 128. Each chunk is therefore 64 bytes long, so the loads at
+        // offsets 0 to 48 are in bounds. `pos` is a multiple of 64 below `len` (the blocks with an
+        // even index go to the first half of the output, the odd ones to the second half), so the
+        // 16-byte stores at `pos` to `pos + 48` are within both outputs.
+        unsafe {
+            let (p0, p1) = (chunk0.as_ptr(), chunk1.as_ptr());
+            a = _mm256_xor_si256(a, _mm256_loadu2_m128i(p1.cast(), p0.cast()));
+            b = _mm256_xor_si256(b, _mm256_loadu2_m128i(p1.add(16).cast(), p0.add(16).cast()));
+            c = _mm256_xor_si256(c, _mm256_loadu2_m128i(p1.add(32).cast(), p0.add(32).cast()));
+            d = _mm256_xor_si256(d, _mm256_loadu2_m128i(p1.add(48).cast(), p0.add(48).cast()));
+
+            let saves = [a, b, c, d];
+
+            for _ in 0..8 {
+                b = _mm256_xor_si256(b, mm256_rol_epi32x!(_mm256_add_epi32(a, d), 7));
+                c = _mm256_xor_si256(c, mm256_rol_epi32x!(_mm256_add_epi32(b, a), 9));
+                d = _mm256_xor_si256(d, mm256_rol_epi32x!(_mm256_add_epi32(c, b), 13));
+                a = _mm256_xor_si256(a, mm256_rol_epi32x!(_mm256_add_epi32(d, c), 18));
+
+                // Shuffles operate on each 128-bit lane independently, as in the SSE2 version
+                d = _mm256_shuffle_epi32(d, 0b00_11_10_01);
+                c = _mm256_shuffle_epi32(c, 0b01_00_11_10);
+                b = _mm256_shuffle_epi32(b, 0b10_01_00_11);
+                (b, d) = (d, b);
+            }
+
+            a = _mm256_add_epi32(a, saves[0]);
+            b = _mm256_add_epi32(b, saves[1]);
+            c = _mm256_add_epi32(c, saves[2]);
+            d = _mm256_add_epi32(d, saves[3]);
+
+            let (q0, q1) = (output0.as_mut_ptr().add(pos), output1.as_mut_ptr().add(pos));
+            _mm256_storeu2_m128i(q1.cast(), q0.cast(), a);
+            _mm256_storeu2_m128i(q1.add(16).cast(), q0.add(16).cast(), b);
+            _mm256_storeu2_m128i(q1.add(32).cast(), q0.add(32).cast(), c);
+            _mm256_storeu2_m128i(q1.add(48).cast(), q0.add(48).cast(), d);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+/// Execute the `BlockMix` operation.
+///
+/// # Panics
+/// Panics if `input` and `output` don't have the same length, or if that length isn't a non-zero
+/// multiple of 128.
+pub(crate) fn scrypt_block_mix(input: &[u8], output: &mut [u8]) {
+    use core::arch::aarch64::*;
+
+    macro_rules! vrolq_n_u32 {
+        ($w:expr, $amt:literal) => {{
+            let w = $w;
+            vsriq_n_u32::<{ 32 - $amt }>(vshlq_n_u32::<$amt>(w), w)
+        }};
+    }
+
+    assert!(
+        !input.is_empty() && input.len() % 128 == 0,
+        "input must be a non-zero multiple of 128"
+    );
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "output must be the same length as input"
+    );
+
+    let last_block = &input[input.len() - 64..];
+
+    // SAFETY: NEON is enabled at compile time, and `last_block` is 64 bytes long, so the unaligned
+    // 16-byte loads at offsets 0, 16, 32 and 48 are in bounds.
+    let mut a = unsafe { vld1q_u32(last_block.as_ptr().cast()) };
+    // SAFETY: see above.
+    let mut b = unsafe { vld1q_u32(last_block.as_ptr().add(16).cast()) };
+    // SAFETY: see above.
+    let mut c = unsafe { vld1q_u32(last_block.as_ptr().add(32).cast()) };
+    // SAFETY: see above.
+    let mut d = unsafe { vld1q_u32(last_block.as_ptr().add(48).cast()) };
+
+    for (i, chunk) in input.chunks(64).enumerate() {
+        let pos = if i % 2 == 0 {
+            (i / 2) * 64
+        } else {
+            (i / 2) * 64 + input.len() / 2
+        };
+
+        // SAFETY: NEON is enabled at compile time, and we asserted that `input` and `output` have
+        // the same length, a multiple of 128. Each chunk is therefore 64 bytes long, so the loads
+        // at offsets 0 to 48 are in bounds. `pos` is a multiple of 64 below the length (the blocks
+        // with an even index go to the first half of the output, the odd ones to the second half),
+        // so the 16-byte stores at `pos` to `pos + 48` are within `output`.
+        unsafe {
+            a = veorq_u32(a, vld1q_u32(chunk.as_ptr().cast()));
+            b = veorq_u32(b, vld1q_u32(chunk.as_ptr().add(16).cast()));
+            c = veorq_u32(c, vld1q_u32(chunk.as_ptr().add(32).cast()));
+            d = veorq_u32(d, vld1q_u32(chunk.as_ptr().add(48).cast()));
+
+            let saves = [a, b, c, d];
+
+            for _ in 0..8 {
+                b = veorq_u32(b, vrolq_n_u32!(vaddq_u32(a, d), 7));
+                c = veorq_u32(c, vrolq_n_u32!(vaddq_u32(b, a), 9));
+                d = veorq_u32(d, vrolq_n_u32!(vaddq_u32(c, b), 13));
+                a = veorq_u32(a, vrolq_n_u32!(vaddq_u32(d, c), 18));
+
+                d = vextq_u32::<1>(d, d);
+                c = vextq_u32::<2>(c, c);
+                b = vextq_u32::<3>(b, b);
+
+                (b, d) = (d, b);
+            }
+
+            a = vaddq_u32(a, saves[0]);
+            b = vaddq_u32(b, saves[1]);
+            c = vaddq_u32(c, saves[2]);
+            d = vaddq_u32(d, saves[3]);
+
+            vst1q_u32(output.as_mut_ptr().add(pos).cast(), a);
+            vst1q_u32(output.as_mut_ptr().add(pos + 16).cast(), b);
+            vst1q_u32(output.as_mut_ptr().add(pos + 32).cast(), c);
+            vst1q_u32(output.as_mut_ptr().add(pos + 48).cast(), d);
+        }
+    }
+}
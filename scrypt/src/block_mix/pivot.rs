@@ -1,8 +1,10 @@
+#![allow(clippy::unwrap_used, reason = "switch to `as_chunks` when MSRV 1.88")]
+
 /// Permute Salsa20 block to diagonal order
-pub(crate) const PIVOT_ABCD: [usize; 16] = [0, 5, 10, 15, 4, 9, 14, 3, 8, 13, 2, 7, 12, 1, 6, 11];
+const PIVOT_ABCD: [usize; 16] = [0, 5, 10, 15, 4, 9, 14, 3, 8, 13, 2, 7, 12, 1, 6, 11];
 
 /// Inverse of `PIVOT_ABCD`.
-pub(crate) const INVERSE_PIVOT_ABCD: [usize; 16] = const {
+const INVERSE_PIVOT_ABCD: [usize; 16] = const {
     let mut index = [0; 16];
     let mut i = 0;
     while i < 16 {
@@ -18,3 +20,29 @@ pub(crate) const INVERSE_PIVOT_ABCD: [usize; 16] = const {
     }
     index
 };
+
+/// Permute each Salsa20 block in `b` to diagonal order.
+pub(crate) fn shuffle_in(b: &mut [u8]) {
+    for chunk in b.chunks_exact_mut(64) {
+        let mut t = [0u32; 16];
+        for (c, b) in chunk.chunks_exact(4).zip(t.iter_mut()) {
+            *b = u32::from_ne_bytes(c.try_into().unwrap());
+        }
+        chunk.chunks_exact_mut(4).enumerate().for_each(|(i, b)| {
+            b.copy_from_slice(&t[PIVOT_ABCD[i]].to_ne_bytes());
+        });
+    }
+}
+
+/// Permute each Salsa20 block in `b` back from diagonal order.
+pub(crate) fn shuffle_out(b: &mut [u8]) {
+    for chunk in b.chunks_exact_mut(64) {
+        let mut t = [0u32; 16];
+        for (c, b) in chunk.chunks_exact(4).zip(t.iter_mut()) {
+            *b = u32::from_ne_bytes(c.try_into().unwrap());
+        }
+        chunk.chunks_exact_mut(4).enumerate().for_each(|(i, b)| {
+            b.copy_from_slice(&t[INVERSE_PIVOT_ABCD[i]].to_ne_bytes());
+        });
+    }
+}
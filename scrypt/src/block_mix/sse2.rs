@@ -1,31 +1,3 @@
-#![allow(clippy::unwrap_used, reason = "switch to `as_chunks` when MSRV 1.88")]
-
-use crate::block_mix::pivot::{INVERSE_PIVOT_ABCD, PIVOT_ABCD};
-
-pub(crate) fn shuffle_in(b: &mut [u8]) {
-    for chunk in b.chunks_exact_mut(64) {
-        let mut t = [0u32; 16];
-        for (c, b) in chunk.chunks_exact(4).zip(t.iter_mut()) {
-            *b = u32::from_ne_bytes(c.try_into().unwrap());
-        }
-        chunk.chunks_exact_mut(4).enumerate().for_each(|(i, b)| {
-            b.copy_from_slice(&t[PIVOT_ABCD[i]].to_ne_bytes());
-        });
-    }
-}
-
-pub(crate) fn shuffle_out(b: &mut [u8]) {
-    for chunk in b.chunks_exact_mut(64) {
-        let mut t = [0u32; 16];
-        for (c, b) in chunk.chunks_exact(4).zip(t.iter_mut()) {
-            *b = u32::from_ne_bytes(c.try_into().unwrap());
-        }
-        chunk.chunks_exact_mut(4).enumerate().for_each(|(i, b)| {
-            b.copy_from_slice(&t[INVERSE_PIVOT_ABCD[i]].to_ne_bytes());
-        });
-    }
-}
-
 #[allow(clippy::undocumented_unsafe_blocks, reason = "TODO")]
 pub(crate) fn scrypt_block_mix(input: &[u8], output: &mut [u8]) {
     #[cfg(target_arch = "x86")]
@@ -0,0 +1,382 @@
+//! Encryption and decryption of files in the format used by Colin Percival's [`scrypt`] utility.
+//!
+//! The files produced by `scrypt enc` consist of:
+//!
+//! - a 96-byte [`FileHeader`] containing the params and salt used to derive a 64-byte key from
+//!   the password, authenticated with the second half of that key,
+//! - the data, encrypted with AES-256 in CTR mode using the first half of the derived key and a
+//!   counter starting at zero,
+//! - an HMAC-SHA256 of the header and the encrypted data, keyed with the second half of the
+//!   derived key.
+//!
+//! # Usage
+//!
+//! ```
+//! # fn main() -> Result<(), Box<dyn core::error::Error>> {
+//! use scrypt::{Params, VerifierLimits, container};
+//!
+//! let password = b"hunter42"; // Bad password; don't actually use!
+//! let salt = [0x2a; 32]; // Use a random salt for each file
+//! let params = Params::new(10, 8, 1)?;
+//!
+//! let mut encrypted = Vec::new();
+//! container::encrypt(password, &salt, &params, &b"secret data"[..], &mut encrypted)?;
+//!
+//! let mut decrypted = Vec::new();
+//! let limits = VerifierLimits {
+//!     max_memory: 64 * 1024 * 1024,
+//!     ..VerifierLimits::NONE
+//! };
+//! container::decrypt(password, &limits, &encrypted[..], &mut decrypted)?;
+//! assert_eq!(decrypted, b"secret data");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`scrypt`]: https://www.tarsnap.com/scrypt.html
+
+use crate::{
+    Parallelism, Params, VerifierLimits,
+    errors::ContainerError,
+    formats::{FileHeader, hmac_sha256},
+    scrypt_with_parallelism,
+};
+use aes::Aes256;
+use ctr::{
+    Ctr128BE,
+    cipher::{KeyIvInit, StreamCipher},
+};
+use hmac::Mac;
+use std::io::{self, Read, Write};
+use zeroize::Zeroizing;
+
+/// Size of the chunks the data is processed in.
+const CHUNK_SIZE: usize = 8192;
+
+/// Length of the HMAC-SHA256 at the end of the encrypted data.
+const MAC_LEN: usize = 32;
+
+/// AES-256 in CTR mode with a big-endian 128-bit counter.
+type Aes256Ctr = Ctr128BE<Aes256>;
+
+/// Encrypt the data read from `reader` with a key derived from `password`, writing the header,
+/// the encrypted data and the HMAC to `writer`.
+///
+/// A random `salt` should be used for each file.
+///
+/// # Errors
+/// Returns [`ContainerError::Io`] if reading or writing fails.
+pub fn encrypt(
+    password: &[u8],
+    salt: &[u8; FileHeader::SALT_LEN],
+    params: &Params,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<(), ContainerError> {
+    let params = Params {
+        len: None,
+        ..*params
+    };
+    let derived_key = derive_key(password, salt, &params);
+    let header = FileHeader::new_with_derived_key(&derived_key, salt, &params);
+    writer.write_all(header.as_bytes())?;
+
+    let mut cipher = body_cipher(&derived_key);
+    let mut mac = hmac_sha256(&derived_key[32..], header.as_bytes());
+    let mut buf = [0u8; CHUNK_SIZE];
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        cipher.apply_keystream(&mut buf[..n]);
+        mac.update(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+    }
+
+    writer.write_all(&mac.finalize().into_bytes())?;
+    Ok(())
+}
+
+/// Decrypt the file read from `reader` using `password`, writing the decrypted data to `writer`.
+///
+/// The data is decrypted as it is read, so it is written to `writer` before the HMAC at the end of
+/// the file has been checked: if an error is returned, any data already written must be
+/// discarded.
+///
+/// The params are read from the file, so the memory and time used to derive the key are
+/// controlled by its author. Files whose params exceed `limits` are rejected before deriving the
+/// key.
+///
+/// # Errors
+/// - [`ContainerError::InvalidHeader`] if the file does not start with a valid header
+/// - [`ContainerError::ParamsExceedLimits`] if the params in the header exceed `limits`
+/// - [`ContainerError::InvalidPassword`] if the password is incorrect
+/// - [`ContainerError::InvalidMac`] if the encrypted data is truncated or has been modified
+/// - [`ContainerError::Io`] if reading or writing fails
+pub fn decrypt(
+    password: &[u8],
+    limits: &VerifierLimits,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<(), ContainerError> {
+    let mut header = [0u8; FileHeader::LEN];
+    reader
+        .read_exact(&mut header)
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => ContainerError::InvalidHeader,
+            _ => err.into(),
+        })?;
+
+    let header = FileHeader::from_bytes(&header).map_err(|_| ContainerError::InvalidHeader)?;
+    let params = header.params().map_err(|_| ContainerError::InvalidHeader)?;
+    limits
+        .check(&params)
+        .map_err(|_| ContainerError::ParamsExceedLimits)?;
+    let salt = header
+        .salt()
+        .try_into()
+        .map_err(|_| ContainerError::InvalidHeader)?;
+
+    let derived_key = derive_key(password, salt, &params);
+    header
+        .verify_derived_key(&derived_key)
+        .map_err(|_| ContainerError::InvalidPassword)?;
+
+    let mut cipher = body_cipher(&derived_key);
+    let mut mac = hmac_sha256(&derived_key[32..], header.as_bytes());
+
+    // The last `MAC_LEN` bytes read so far may be the HMAC, so they are held back at the start of
+    // the buffer until more data is read.
+    let mut buf = [0u8; MAC_LEN + CHUNK_SIZE];
+    let mut filled = 0;
+
+    loop {
+        let n = match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        filled += n;
+
+        if let Some(len) = filled.checked_sub(MAC_LEN).filter(|&len| len > 0) {
+            let data = &mut buf[..len];
+            mac.update(data);
+            cipher.apply_keystream(data);
+            writer.write_all(data)?;
+
+            buf.copy_within(len..filled, 0);
+            filled = MAC_LEN;
+        }
+    }
+
+    if filled < MAC_LEN {
+        return Err(ContainerError::InvalidMac);
+    }
+
+    mac.verify_slice(&buf[..MAC_LEN])
+        .map_err(|_| ContainerError::InvalidMac)
+}
+
+/// Derive the 64-byte key from the password, which is zeroized when dropped.
+fn derive_key(
+    password: &[u8],
+    salt: &[u8; FileHeader::SALT_LEN],
+    params: &Params,
+) -> Zeroizing<[u8; FileHeader::DERIVED_KEY_LEN]> {
+    let mut derived_key = Zeroizing::new([0u8; FileHeader::DERIVED_KEY_LEN]);
+    scrypt_with_parallelism(
+        password,
+        salt,
+        params,
+        derived_key.as_mut(),
+        Parallelism::default(),
+    )
+    .expect("derived key length is valid");
+    derived_key
+}
+
+/// Initialize the cipher for the data using the first half of the derived key.
+fn body_cipher(derived_key: &[u8; FileHeader::DERIVED_KEY_LEN]) -> Aes256Ctr {
+    Aes256Ctr::new_from_slices(&derived_key[..32], &[0u8; 16]).expect("key and IV are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt};
+    use crate::{Params, VerifierLimits, errors::ContainerError};
+    use alloc::vec::Vec;
+    use base64ct::{Base64, Encoding};
+    use sha2::{Digest, Sha256};
+
+    /// Password used to make the example file.
+    const EXAMPLE_PASSWORD: &[u8] = b"correct horse battery staple";
+
+    /// Contents of the example file.
+    const EXAMPLE_DATA: &[u8] = b"The quick brown fox jumps over the lazy dog.\n";
+
+    /// Example file encrypted with `ln=10`, `r=8`, `p=2` and salt bytes `0x40..0x60`, generated by
+    /// this crate. Its header checksum and HMACs were cross-checked, and its body decrypted, using
+    /// Python's `hashlib.scrypt` and `hmac` modules and AES-256-CTR from the `cryptography`
+    /// package.
+    const EXAMPLE_FILE: &str = "c2NyeXB0AAoAAAAIAAAAAkBBQkNERUZHSElKS0xNTk9QUVJTVFVWV1hZWltcXV5f0X2Fg5VuoTtRXmsswIcDKYHwteUOAlNCBUliXwYftL2/AnMa6fA4r/g9McfwG0Olnmm1JSbLvJRs0TecaFsAQybuvZ+X3PJa4xVfZ/T6rgbovz2kvr9puSX7cXZeKrobJ0i7RVLtuPxxdN7ORASV+UvnJTIrJj//XfK+9tU=";
+
+    fn example_file() -> Vec<u8> {
+        let mut buf = [0u8; 96 + EXAMPLE_DATA.len() + 32];
+        Base64::decode(EXAMPLE_FILE, &mut buf).unwrap().to_vec()
+    }
+
+    #[test]
+    fn encrypt_example() {
+        let salt: [u8; 32] = core::array::from_fn(|i| u8::try_from(0x40 + i).unwrap());
+        let params = Params::new(10, 8, 2).unwrap();
+
+        let mut encrypted = Vec::new();
+        encrypt(
+            EXAMPLE_PASSWORD,
+            &salt,
+            &params,
+            EXAMPLE_DATA,
+            &mut encrypted,
+        )
+        .unwrap();
+        assert_eq!(encrypted, example_file());
+    }
+
+    #[test]
+    fn decrypt_example() {
+        let mut decrypted = Vec::new();
+        decrypt(
+            EXAMPLE_PASSWORD,
+            &VerifierLimits::NONE,
+            &example_file()[..],
+            &mut decrypted,
+        )
+        .unwrap();
+        assert_eq!(decrypted, EXAMPLE_DATA);
+    }
+
+    #[test]
+    fn round_trip_multiple_chunks() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| i.to_le_bytes()[0]).collect();
+        let params = Params::new(4, 8, 1).unwrap();
+
+        let mut encrypted = Vec::new();
+        encrypt(
+            EXAMPLE_PASSWORD,
+            &[0; 32],
+            &params,
+            &data[..],
+            &mut encrypted,
+        )
+        .unwrap();
+        assert_eq!(encrypted.len(), 96 + data.len() + 32);
+
+        let mut decrypted = Vec::new();
+        decrypt(
+            EXAMPLE_PASSWORD,
+            &VerifierLimits::NONE,
+            &encrypted[..],
+            &mut decrypted,
+        )
+        .unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn decrypt_wrong_password() {
+        let result = decrypt(
+            b"bogus",
+            &VerifierLimits::NONE,
+            &example_file()[..],
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(ContainerError::InvalidPassword)));
+    }
+
+    #[test]
+    fn decrypt_exceeding_limits() {
+        let params = Params::new(10, 8, 2).unwrap();
+        let limits = VerifierLimits {
+            max_memory: params.memory_required(),
+            max_log_n: 10,
+            max_p: 2,
+        };
+
+        let mut decrypted = Vec::new();
+        decrypt(
+            EXAMPLE_PASSWORD,
+            &limits,
+            &example_file()[..],
+            &mut decrypted,
+        )
+        .unwrap();
+        assert_eq!(decrypted, EXAMPLE_DATA);
+
+        for limits in [
+            VerifierLimits {
+                max_memory: params.memory_required() - 1,
+                ..limits
+            },
+            VerifierLimits {
+                max_log_n: 9,
+                ..limits
+            },
+            VerifierLimits { max_p: 1, ..limits },
+        ] {
+            let result = decrypt(EXAMPLE_PASSWORD, &limits, &example_file()[..], Vec::new());
+            assert!(matches!(result, Err(ContainerError::ParamsExceedLimits)));
+        }
+    }
+
+    #[test]
+    fn decrypt_oversized_header() {
+        // Claim `ln=40`, which would need 1 TiB of memory to derive the key
+        let mut file = example_file();
+        file[7] = 40;
+        let checksum = Sha256::digest(&file[..48]);
+        file[48..64].copy_from_slice(&checksum[..16]);
+
+        let limits = VerifierLimits {
+            max_memory: 64 * 1024 * 1024,
+            ..VerifierLimits::NONE
+        };
+        let result = decrypt(EXAMPLE_PASSWORD, &limits, &file[..], Vec::new());
+        assert!(matches!(result, Err(ContainerError::ParamsExceedLimits)));
+    }
+
+    #[test]
+    fn decrypt_modified() {
+        let mut file = example_file();
+        file[100] ^= 1;
+        let result = decrypt(
+            EXAMPLE_PASSWORD,
+            &VerifierLimits::NONE,
+            &file[..],
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(ContainerError::InvalidMac)));
+
+        let file = example_file();
+        let result = decrypt(
+            EXAMPLE_PASSWORD,
+            &VerifierLimits::NONE,
+            &file[..file.len() - 1],
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(ContainerError::InvalidMac)));
+
+        let result = decrypt(
+            EXAMPLE_PASSWORD,
+            &VerifierLimits::NONE,
+            &file[..50],
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(ContainerError::InvalidHeader)));
+    }
+}
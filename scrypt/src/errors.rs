@@ -1,5 +1,8 @@
 use core::fmt;
 
+#[cfg(feature = "container")]
+use std::io;
+
 /// `scrypt()` error
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct InvalidOutputLen;
@@ -8,6 +11,35 @@ pub struct InvalidOutputLen;
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct InvalidParams;
 
+/// `scrypt_with_memory()` and [`VerifierLimits::check`][`crate::VerifierLimits::check`] error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Output buffer length is invalid.
+    InvalidOutputLen,
+    /// Memory buffer is smaller than [`Params::memory_required`][`crate::Params::memory_required`].
+    InvalidMemoryLen,
+    /// Parameters exceed the configured [`VerifierLimits`][`crate::VerifierLimits`].
+    ParamsExceedLimits,
+}
+
+/// [`container`][`crate::container`] error
+#[cfg(feature = "container")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ContainerError {
+    /// I/O error reading or writing the data.
+    Io(io::Error),
+    /// Input is not a valid scrypt-encrypted file.
+    InvalidHeader,
+    /// Password is incorrect.
+    InvalidPassword,
+    /// Encrypted data is truncated or has been modified.
+    InvalidMac,
+    /// Parameters in the header exceed the [`VerifierLimits`][`crate::VerifierLimits`].
+    ParamsExceedLimits,
+}
+
 impl fmt::Display for InvalidOutputLen {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("invalid output buffer length")
@@ -30,3 +62,69 @@ impl fmt::Display for InvalidParams {
 }
 
 impl core::error::Error for InvalidParams {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidOutputLen => InvalidOutputLen.fmt(f),
+            Error::InvalidMemoryLen => f.write_str("invalid memory buffer length"),
+            Error::ParamsExceedLimits => f.write_str("parameters exceed limits"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<InvalidOutputLen> for Error {
+    fn from(_err: InvalidOutputLen) -> Error {
+        Error::InvalidOutputLen
+    }
+}
+
+#[cfg(feature = "kdf")]
+impl From<Error> for kdf::Error {
+    fn from(_err: Error) -> kdf::Error {
+        kdf::Error
+    }
+}
+
+#[cfg(feature = "password-hash")]
+impl From<Error> for password_hash::Error {
+    fn from(err: Error) -> password_hash::Error {
+        match err {
+            Error::InvalidOutputLen => password_hash::Error::OutputSize,
+            Error::InvalidMemoryLen => password_hash::Error::Internal,
+            Error::ParamsExceedLimits => password_hash::Error::ParamsInvalid,
+        }
+    }
+}
+
+#[cfg(feature = "container")]
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::Io(err) => err.fmt(f),
+            ContainerError::InvalidHeader => f.write_str("invalid scrypt file header"),
+            ContainerError::InvalidPassword => f.write_str("invalid password"),
+            ContainerError::InvalidMac => f.write_str("encrypted data is corrupt"),
+            ContainerError::ParamsExceedLimits => f.write_str("parameters exceed limits"),
+        }
+    }
+}
+
+#[cfg(feature = "container")]
+impl core::error::Error for ContainerError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ContainerError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "container")]
+impl From<io::Error> for ContainerError {
+    fn from(err: io::Error) -> ContainerError {
+        ContainerError::Io(err)
+    }
+}
@@ -0,0 +1,332 @@
+//! Interoperability with the scrypt password hash formats used by other implementations.
+//!
+//! [`Scrypt`] implements [`PasswordVerifier<str>`], detecting the format of the hash:
+//!
+//! - PHC strings which begin with `$scrypt$`, as produced by e.g. [passlib]'s `scrypt` handler.
+//! - Modular Crypt Format (MCF) strings which begin with `$7$`, as produced by libxcrypt, or by
+//!   libsodium's `crypto_pwhash_scryptsalsa208sha256_str`. Both use the same encoding, in which
+//!   the encoded salt itself is used as the scrypt salt.
+//! - Base64-encoded headers of files encrypted by Colin Percival's [`scrypt`] utility, see
+//!   [`FileHeader`].
+//!
+//! [passlib]: https://passlib.readthedocs.io/en/stable/lib/passlib.hash.scrypt.html
+//! [`scrypt`]: https://www.tarsnap.com/scrypt.html
+
+use crate::{Params, Scrypt, mcf, phc, scrypt_with_parallelism};
+use base64ct::{Base64, Encoding};
+use core::{fmt, str::FromStr};
+use hmac::{Hmac, KeyInit, Mac};
+use password_hash::{Error, PasswordVerifier, Result};
+use sha2::{Digest, Sha256};
+
+/// Magic bytes at the start of a [`FileHeader`].
+const MAGIC: &[u8; 6] = b"scrypt";
+
+/// Version of the file format.
+const VERSION: u8 = 0;
+
+/// Key derived from the password, which is zeroized when dropped if the `zeroize` feature is
+/// enabled.
+#[cfg(feature = "zeroize")]
+type DerivedKey = zeroize::Zeroizing<[u8; FileHeader::DERIVED_KEY_LEN]>;
+
+/// Key derived from the password.
+#[cfg(not(feature = "zeroize"))]
+type DerivedKey = [u8; FileHeader::DERIVED_KEY_LEN];
+
+/// Header of files encrypted by Colin Percival's `scrypt` utility:
+///
+/// ```text
+/// offset  length  contents
+///      0       6  "scrypt"
+///      6       1  format version (0)
+///      7       1  log2(N)
+///      8       4  r (big-endian)
+///     12       4  p (big-endian)
+///     16      32  salt
+///     48      16  first 16 bytes of SHA256(bytes 0 to 47)
+///     64      32  HMAC-SHA256(bytes 0 to 63), keyed with the second half of the derived key
+/// ```
+///
+/// The derived key is the 64-byte output of scrypt for the password and salt. Since the header
+/// is authenticated using the derived key, it can be used to verify the password. Its string
+/// representation is the Base64 encoding of the header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileHeader {
+    /// Header bytes
+    bytes: [u8; Self::LEN],
+}
+
+impl FileHeader {
+    /// Length of the header in bytes.
+    pub const LEN: usize = 96;
+
+    /// Length of the salt in bytes.
+    pub const SALT_LEN: usize = 32;
+
+    /// Length of the key derived from the password in bytes.
+    pub const DERIVED_KEY_LEN: usize = 64;
+
+    /// Compute the header for the given password, salt, and params.
+    ///
+    /// Any output length the params have been customized with is ignored.
+    #[allow(
+        clippy::missing_panics_doc,
+        reason = "derived key length is always valid"
+    )]
+    #[must_use]
+    pub fn new(password: &[u8], salt: &[u8; Self::SALT_LEN], params: &Params) -> Self {
+        let params = Params {
+            len: None,
+            ..*params
+        };
+
+        let mut derived_key = DerivedKey::from([0u8; Self::DERIVED_KEY_LEN]);
+        scrypt_with_parallelism(
+            password,
+            salt,
+            &params,
+            derived_key.as_mut_slice(),
+            Default::default(),
+        )
+        .expect("derived key length is valid");
+
+        Self::new_with_derived_key(&derived_key, salt, &params)
+    }
+
+    /// Compute the header for a key already derived from the password using the given salt and
+    /// params.
+    pub(crate) fn new_with_derived_key(
+        derived_key: &[u8; Self::DERIVED_KEY_LEN],
+        salt: &[u8; Self::SALT_LEN],
+        params: &Params,
+    ) -> Self {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..6].copy_from_slice(MAGIC);
+        bytes[6] = VERSION;
+        bytes[7] = params.log_n;
+        bytes[8..12].copy_from_slice(&params.r.to_be_bytes());
+        bytes[12..16].copy_from_slice(&params.p.to_be_bytes());
+        bytes[16..48].copy_from_slice(salt);
+
+        let checksum = Sha256::digest(&bytes[..48]);
+        bytes[48..64].copy_from_slice(&checksum[..16]);
+
+        let signature = hmac_sha256(&derived_key[32..], &bytes[..64]).finalize();
+        bytes[64..].copy_from_slice(&signature.into_bytes());
+
+        Self { bytes }
+    }
+
+    /// Parse a header, checking its magic bytes, version, params, and checksum.
+    ///
+    /// # Errors
+    /// Returns [`Error::EncodingInvalid`] if the header is malformed, or [`Error::ParamsInvalid`]
+    /// if its params are invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; Self::LEN] = bytes.try_into().map_err(|_| Error::EncodingInvalid)?;
+
+        if &bytes[..6] != MAGIC || bytes[6] != VERSION {
+            return Err(Error::EncodingInvalid);
+        }
+
+        if Sha256::digest(&bytes[..48])[..16] != bytes[48..64] {
+            return Err(Error::EncodingInvalid);
+        }
+
+        let header = Self { bytes };
+        header.params()?;
+        Ok(header)
+    }
+
+    /// Get the header bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.bytes
+    }
+
+    /// Get the scrypt params.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParamsInvalid`] if the params are invalid. As in the `scrypt` utility,
+    /// this includes `log2(N) = 0`.
+    pub fn params(&self) -> Result<Params> {
+        if self.bytes[7] == 0 {
+            return Err(Error::ParamsInvalid);
+        }
+
+        let r = u32::from_be_bytes([self.bytes[8], self.bytes[9], self.bytes[10], self.bytes[11]]);
+        let p = u32::from_be_bytes([
+            self.bytes[12],
+            self.bytes[13],
+            self.bytes[14],
+            self.bytes[15],
+        ]);
+        Params::new(self.bytes[7], r, p).map_err(|_| Error::ParamsInvalid)
+    }
+
+    /// Get the salt.
+    #[must_use]
+    pub fn salt(&self) -> &[u8] {
+        &self.bytes[16..48]
+    }
+
+    /// Check that the header was computed using the given derived key.
+    pub(crate) fn verify_derived_key(
+        &self,
+        derived_key: &[u8; Self::DERIVED_KEY_LEN],
+    ) -> Result<()> {
+        hmac_sha256(&derived_key[32..], &self.bytes[..64])
+            .verify_slice(&self.bytes[64..])
+            .map_err(|_| Error::PasswordInvalid)
+    }
+}
+
+impl FromStr for FileHeader {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut bytes = [0u8; Self::LEN];
+        let bytes = Base64::decode(s, &mut bytes).map_err(|_| Error::EncodingInvalid)?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl fmt::Display for FileHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; Self::LEN * 4 / 3];
+        f.write_str(Base64::encode(&self.bytes, &mut buf).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl PasswordVerifier<FileHeader> for Scrypt {
+    fn verify_password(&self, password: &[u8], hash: &FileHeader) -> Result<()> {
+        let params = hash.params()?;
+        self.verifier_limits.check(&params)?;
+
+        let mut derived_key = DerivedKey::from([0u8; FileHeader::DERIVED_KEY_LEN]);
+        scrypt_with_parallelism(
+            password,
+            hash.salt(),
+            &params,
+            derived_key.as_mut_slice(),
+            self.parallelism,
+        )
+        .map_err(|_| Error::OutputSize)?;
+
+        hash.verify_derived_key(&derived_key)
+    }
+}
+
+impl PasswordVerifier<str> for Scrypt {
+    fn verify_password(&self, password: &[u8], hash: &str) -> Result<()> {
+        if hash.starts_with("$scrypt$") {
+            self.verify_password(password, &phc::PasswordHash::new(hash)?)
+        } else if hash.starts_with("$7$") {
+            let hash = mcf::PasswordHashRef::new(hash).map_err(|_| Error::EncodingInvalid)?;
+            self.verify_password(password, hash)
+        } else {
+            self.verify_password(password, &hash.parse::<FileHeader>()?)
+        }
+    }
+}
+
+/// Initialize HMAC-SHA256 with the given key and message.
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> Hmac<Sha256> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    hmac.update(message);
+    hmac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileHeader;
+    use crate::{Params, Scrypt};
+    use alloc::string::ToString;
+    use password_hash::{Error, PasswordVerifier};
+
+    /// Password used to make the example hashes.
+    const EXAMPLE_PASSWORD: &[u8] = b"correct horse battery staple";
+
+    /// passlib `scrypt` hash of `"password"`.
+    const EXAMPLE_PASSLIB_HASH: &str =
+        "$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E";
+
+    /// libsodium `crypto_pwhash_scryptsalsa208sha256_str` format hash with a 32-byte salt,
+    /// `ln=10`, `r=8`, `p=1`.
+    const EXAMPLE_LIBSODIUM_HASH: &str = "$7$86..../....U2W6XEG7aQ08dcm8goW9j.HAmA1BpMnBsYXCvkHDyw1$awzxBQFg0Dh5EDeOoHEmPxHrdprUhl3PB5uJc.Pim01";
+
+    /// Encrypted file header with `ln=10`, `r=8`, `p=2`, generated by this crate. Its checksum and
+    /// HMAC were cross-checked using Python's `hashlib.scrypt` and `hmac` modules.
+    const EXAMPLE_FILE_HEADER: &str = "c2NyeXB0AAoAAAAIAAAAAkBBQkNERUZHSElKS0xNTk9QUVJTVFVWV1hZWltcXV5f0X2Fg5VuoTtRXmsswIcDKYHwteUOAlNCBUliXwYftL2/AnMa6fA4r/g9McfwG0Ol";
+
+    #[test]
+    fn verify_passlib() {
+        assert_eq!(
+            Scrypt::new().verify_password(b"password", EXAMPLE_PASSLIB_HASH),
+            Ok(())
+        );
+        assert_eq!(
+            Scrypt::new().verify_password(b"bogus", EXAMPLE_PASSLIB_HASH),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn verify_libsodium() {
+        assert_eq!(
+            Scrypt::new().verify_password(EXAMPLE_PASSWORD, EXAMPLE_LIBSODIUM_HASH),
+            Ok(())
+        );
+        assert_eq!(
+            Scrypt::new().verify_password(b"bogus", EXAMPLE_LIBSODIUM_HASH),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn verify_file_header() {
+        let header: FileHeader = EXAMPLE_FILE_HEADER.parse().unwrap();
+        assert_eq!(header.params().unwrap(), Params::new(10, 8, 2).unwrap());
+        assert_eq!(header.to_string(), EXAMPLE_FILE_HEADER);
+
+        assert_eq!(
+            Scrypt::new().verify_password(EXAMPLE_PASSWORD, EXAMPLE_FILE_HEADER),
+            Ok(())
+        );
+        assert_eq!(
+            Scrypt::new().verify_password(b"bogus", EXAMPLE_FILE_HEADER),
+            Err(Error::PasswordInvalid)
+        );
+    }
+
+    #[test]
+    fn file_header_round_trip() {
+        let salt: [u8; 32] = core::array::from_fn(|i| u8::try_from(0x40 + i).unwrap());
+        let params = Params::new(10, 8, 2).unwrap();
+
+        let header = FileHeader::new(EXAMPLE_PASSWORD, &salt, &params);
+        assert_eq!(header.to_string(), EXAMPLE_FILE_HEADER);
+    }
+
+    #[test]
+    fn file_header_bad_checksum() {
+        let mut bytes = *EXAMPLE_FILE_HEADER
+            .parse::<FileHeader>()
+            .unwrap()
+            .as_bytes();
+        bytes[7] ^= 1;
+        assert_eq!(FileHeader::from_bytes(&bytes), Err(Error::EncodingInvalid));
+    }
+
+    #[test]
+    fn file_header_log_n_zero() {
+        let salt = [0u8; 32];
+        let header = FileHeader::new(EXAMPLE_PASSWORD, &salt, &Params::new(0, 8, 1).unwrap());
+        assert_eq!(header.params(), Err(Error::ParamsInvalid));
+        assert_eq!(
+            FileHeader::from_bytes(header.as_bytes()),
+            Err(Error::ParamsInvalid)
+        );
+    }
+}
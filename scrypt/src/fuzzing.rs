@@ -0,0 +1,59 @@
+//! Internals exposed for fuzzing. Not part of the public API.
+
+use crate::block_mix;
+
+#[cfg(feature = "mcf")]
+use crate::{Params, mcf};
+
+/// Compute `BlockMix` of `input` using the backend selected for the target.
+///
+/// `input` is shuffled into the layout used by the backend and back, and `output` is shuffled out.
+pub fn scrypt_block_mix(input: &mut [u8], output: &mut [u8]) {
+    block_mix::shuffle_in(input);
+    block_mix::scrypt_block_mix(input, output);
+    block_mix::shuffle_out(input);
+    block_mix::shuffle_out(output);
+}
+
+/// Compute `BlockMix` of two inputs at once using the backend selected for the target, as in
+/// [`scrypt_block_mix`].
+pub fn scrypt_block_mix_x2(
+    input0: &mut [u8],
+    output0: &mut [u8],
+    input1: &mut [u8],
+    output1: &mut [u8],
+) {
+    block_mix::shuffle_in(input0);
+    block_mix::shuffle_in(input1);
+    block_mix::scrypt_block_mix_x2(input0, output0, input1, output1);
+    block_mix::shuffle_out(input0);
+    block_mix::shuffle_out(input1);
+    block_mix::shuffle_out(output0);
+    block_mix::shuffle_out(output1);
+}
+
+/// Compute `BlockMix` of `input` using the portable backend.
+pub fn scrypt_block_mix_soft(input: &[u8], output: &mut [u8]) {
+    block_mix::soft_test::scrypt_block_mix(input, output);
+}
+
+/// Decode the params and salt of an MCF hash, as in the field following `$7$`.
+///
+/// # Errors
+/// Returns an error if the params or salt are invalid.
+#[cfg(feature = "mcf")]
+pub fn mcf_decode_params_and_salt(s: &str) -> password_hash::Result<(Params, &[u8])> {
+    mcf::decode_params_and_salt(s)
+}
+
+/// Encode params as they appear at the start of the field following `$7$`.
+///
+/// # Errors
+/// Returns an error if the params can't be encoded.
+#[cfg(feature = "mcf")]
+pub fn mcf_encode_params(
+    params: Params,
+    out: &mut [u8; mcf::PARAMS_LEN],
+) -> password_hash::Result<&str> {
+    mcf::encode_params(params, out)
+}
@@ -0,0 +1,187 @@
+//! Incremental computation of scrypt, for progress reporting and cancellation.
+
+use crate::{Params, block_mix, check_output_len, errors, romix};
+use alloc::vec::Vec;
+use core::fmt;
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// scrypt computation which can be run a few steps at a time.
+///
+/// Computing scrypt with large params can take seconds, during which [`scrypt`][`crate::scrypt`]
+/// blocks without feedback. This type instead lets the caller run a bounded number of steps at a
+/// time with [`IncrementalScrypt::run`], in between which it can report progress using
+/// [`IncrementalScrypt::percent_complete`] or cancel the computation by dropping it.
+///
+/// Each step computes one `BlockMix` operation: there are `2 * N` steps in each of the `p` lanes,
+/// which are computed one after the other. The output is identical to that of
+/// [`scrypt`][`crate::scrypt`].
+///
+/// When the `zeroize` feature is enabled, the working memory is zeroized on drop.
+///
+/// ```
+/// use scrypt::{IncrementalScrypt, Params};
+///
+/// let params = Params::new(10, 8, 1).unwrap();
+/// let mut scrypt = IncrementalScrypt::new(b"password", b"salt", &params);
+///
+/// while !scrypt.run(256) {
+///     println!("{}%", scrypt.percent_complete());
+/// }
+///
+/// let mut output = [0u8; 32];
+/// scrypt.finish(b"password", &mut output).unwrap();
+/// ```
+pub struct IncrementalScrypt {
+    /// Params being computed
+    params: Params,
+
+    /// Working memory, as laid out by [`scrypt_with_memory`][`crate::scrypt_with_memory`]
+    memory: Vec<u8>,
+
+    /// Index of the lane being computed
+    lane: usize,
+
+    /// Number of steps computed in the current lane
+    step: usize,
+}
+
+impl IncrementalScrypt {
+    /// Start computing scrypt for the given password, salt, and params.
+    ///
+    /// This allocates [`Params::memory_required`] bytes of working memory and computes the
+    /// initial PBKDF2 step.
+    #[must_use]
+    pub fn new(password: &[u8], salt: &[u8], params: &Params) -> Self {
+        let mut memory = vec![0u8; params.memory_required()];
+        let pr128 = (params.p as usize) * (params.r as usize) * 128;
+        pbkdf2_hmac::<Sha256>(password, salt, 1, &mut memory[..pr128]);
+
+        Self {
+            params: *params,
+            memory,
+            lane: 0,
+            step: 0,
+        }
+    }
+
+    /// Run at most `max_steps` steps of the computation.
+    ///
+    /// Returns `true` if all steps have been computed, in which case the output can be obtained
+    /// using [`IncrementalScrypt::finish`].
+    pub fn run(&mut self, max_steps: u64) -> bool {
+        let n = 1 << self.params.log_n;
+        let p = self.params.p as usize;
+        let r128 = (self.params.r as usize) * 128;
+        let pr128 = p * r128;
+        let nr128 = n * r128;
+
+        let (b, rest) = self.memory.split_at_mut(pr128);
+        let (v, t) = rest.split_at_mut(nr128);
+        let mut remaining = usize::try_from(max_steps).unwrap_or(usize::MAX);
+
+        while remaining > 0 && self.lane < p {
+            let lane = &mut b[self.lane * r128..(self.lane + 1) * r128];
+
+            if self.step == 0 {
+                block_mix::shuffle_in(lane);
+            }
+
+            let steps = if self.step < n {
+                let steps = remaining.min(n - self.step);
+                romix::scrypt_ro_mix_fill(lane, v, self.step..self.step + steps);
+                steps
+            } else {
+                let steps = remaining.min(2 * n - self.step);
+                romix::scrypt_ro_mix_mix(lane, v, t, n, steps);
+                steps
+            };
+
+            self.step += steps;
+            remaining -= steps;
+
+            if self.step == 2 * n {
+                block_mix::shuffle_out(lane);
+                self.lane += 1;
+                self.step = 0;
+            }
+        }
+
+        self.is_done()
+    }
+
+    /// Whether all steps have been computed.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.lane == self.params.p as usize
+    }
+
+    /// Total number of steps: `2 * N * p`.
+    #[must_use]
+    pub fn total_steps(&self) -> u64 {
+        self.params
+            .n()
+            .saturating_mul(2)
+            .saturating_mul(self.params.p.into())
+    }
+
+    /// Number of steps computed so far.
+    #[must_use]
+    pub fn steps_done(&self) -> u64 {
+        let lane = u64::try_from(self.lane).unwrap_or(u64::MAX);
+        let step = u64::try_from(self.step).unwrap_or(u64::MAX);
+        self.params
+            .n()
+            .saturating_mul(2)
+            .saturating_mul(lane)
+            .saturating_add(step)
+    }
+
+    /// Percentage of steps computed so far, from 0 to 100.
+    #[must_use]
+    pub fn percent_complete(&self) -> u8 {
+        let percent = u128::from(self.steps_done()) * 100 / u128::from(self.total_steps());
+        u8::try_from(percent).unwrap_or(100)
+    }
+
+    /// Compute any remaining steps, and write the output derived from `password` to `output`.
+    ///
+    /// `password` must be the same as the one passed to [`IncrementalScrypt::new`].
+    ///
+    /// # Errors
+    /// Returns [`errors::InvalidOutputLen`] under the same conditions as
+    /// [`scrypt`][`crate::scrypt`], without computing the remaining steps.
+    pub fn finish(
+        mut self,
+        password: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), errors::InvalidOutputLen> {
+        check_output_len(&self.params, output)?;
+
+        while !self.run(u64::MAX) {}
+
+        let pr128 = (self.params.p as usize) * (self.params.r as usize) * 128;
+        pbkdf2_hmac::<Sha256>(password, &self.memory[..pr128], 1, output);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for IncrementalScrypt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncrementalScrypt")
+            .field("params", &self.params)
+            .field("lane", &self.lane)
+            .field("step", &self.step)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for IncrementalScrypt {
+    fn drop(&mut self) {
+        self.memory.as_mut_slice().zeroize();
+    }
+}
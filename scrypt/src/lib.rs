@@ -12,9 +12,13 @@
 //!
 //! ```toml
 //! [dependencies]
-//! scrypt = { version = "0.12", default-features = false }
+//! scrypt = { version = "0.12", default-features = false, features = ["alloc"] }
 //! ```
 //!
+//! On targets without a heap, disable the `alloc` feature as well and use
+//! [`scrypt_with_memory`] with a caller-provided buffer of
+//! [`Params::memory_required`] bytes.
+//!
 //! # Usage (simple PHC password hash usage with default params)
 //!
 #![cfg_attr(
@@ -49,24 +53,44 @@
 //! # }
 //! ```
 
+#[cfg(feature = "alloc")]
 #[macro_use]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use pbkdf2::pbkdf2_hmac;
 use sha2::Sha256;
 
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
 mod block_mix;
 /// Errors for `scrypt` operations.
 pub mod errors;
+#[cfg(feature = "alloc")]
+mod incremental;
+#[cfg(feature = "alloc")]
+mod parallelism;
 mod params;
 mod romix;
 
+#[cfg(feature = "container")]
+pub mod container;
+#[cfg(feature = "formats")]
+pub mod formats;
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzzing;
 #[cfg(feature = "mcf")]
 pub mod mcf;
 #[cfg(feature = "phc")]
 pub mod phc;
 
-pub use crate::params::Params;
+pub use crate::params::{Params, VerifierLimits};
+
+#[cfg(feature = "alloc")]
+pub use crate::{incremental::IncrementalScrypt, parallelism::Parallelism};
 
 #[cfg(feature = "kdf")]
 pub use kdf::{self, Kdf, Pbkdf};
@@ -93,60 +117,136 @@ use password_hash::{CustomizedPasswordHasher, PasswordHasher, PasswordVerifier};
 /// ```
 ///
 /// # Note about output lengths
-/// The output size is determined entirely by size of the `output` parameter.
+/// The output size is determined by the size of the `output` parameter.
 ///
 /// If the length of the [`Params`] have been customized using the [`Params::new_with_output_len`]
-/// constructor, that length is ignored and the length of `output` is used instead.
+/// constructor, [`errors::InvalidOutputLen`] is also returned if the length of `output` differs.
+#[cfg(feature = "alloc")]
 pub fn scrypt(
     password: &[u8],
     salt: &[u8],
     params: &Params,
     output: &mut [u8],
 ) -> Result<(), errors::InvalidOutputLen> {
-    // This check required by Scrypt:
-    // check output.len() > 0 && output.len() <= (2^32 - 1) * 32
-    if output.is_empty() || output.len() / 32 > 0xffff_ffff {
-        return Err(errors::InvalidOutputLen);
+    scrypt_with_parallelism(password, salt, params, output, Parallelism::default())
+}
+
+/// Like [`scrypt`], but with control over the number of threads used to compute the `p` lanes
+/// and the memory they may use.
+///
+/// See [`Parallelism`] for details.
+///
+/// # Errors
+/// Returns [`errors::InvalidOutputLen`] under the same conditions as [`scrypt`].
+#[cfg(feature = "alloc")]
+pub fn scrypt_with_parallelism(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    parallelism: Parallelism,
+) -> Result<(), errors::InvalidOutputLen> {
+    check_output_len(params, output)?;
+
+    let plan = parallelism.plan(params);
+
+    if plan.threads <= 1 && !plan.interleave {
+        let mut memory = vec![0u8; params.memory_required()];
+        return scrypt_with_memory(password, salt, params, output, &mut memory)
+            .map_err(|_| errors::InvalidOutputLen);
     }
 
     // The checks in the ScryptParams constructor guarantee
     // that the following is safe:
-    let n = 1 << params.log_n;
-    let r128 = (params.r as usize) * 128;
-    let pr128 = (params.p as usize) * r128;
-    let nr128 = n * r128;
+    let pr128 = (params.p as usize) * (params.r as usize) * 128;
 
     let mut b = vec![0u8; pr128];
     pbkdf2_hmac::<Sha256>(password, salt, 1, &mut b);
+    parallelism::romix_lanes(&mut b, params, plan);
+    pbkdf2_hmac::<Sha256>(password, &b, 1, output);
 
-    #[cfg(not(feature = "parallel"))]
-    romix_sequential(nr128, r128, n, &mut b);
-    #[cfg(feature = "parallel")]
-    romix_parallel(nr128, r128, n, &mut b);
+    #[cfg(feature = "zeroize")]
+    b.as_mut_slice().zeroize();
 
-    pbkdf2_hmac::<Sha256>(password, &b, 1, output);
     Ok(())
 }
 
-#[cfg(not(feature = "parallel"))]
-fn romix_sequential(nr128: usize, r128: usize, n: usize, b: &mut [u8]) {
-    let mut v = vec![0u8; nr128];
-    let mut t = vec![0u8; r128];
+/// Like [`scrypt`], but usable on "heapless" targets.
+///
+/// All working memory is taken from `memory`, which must be at least
+/// [`Params::memory_required`] bytes long. Any additional bytes are left untouched, which allows
+/// a single buffer to be reused across calls with different parameters.
+///
+/// The `p` lanes are always processed one after the other, even when the `parallel` feature is
+/// enabled.
+///
+/// When the `zeroize` feature is enabled, the first [`Params::memory_required`] bytes of `memory`
+/// are zeroized before returning.
+///
+/// # Arguments
+/// - `password` - The password to process as a byte vector
+/// - `salt` - The salt value to use as a byte vector
+/// - `params` - The `ScryptParams` to use
+/// - `output` - The resulting derived key is returned in this byte vector.
+///   **WARNING: Make sure to compare this value in constant time!**
+/// - `memory` - Working memory, at least [`Params::memory_required`] bytes
+///
+/// # Errors
+/// - [`errors::Error::InvalidOutputLen`] if `output` is empty, longer than `(2^32 - 1) * 32`, or
+///   its length differs from the one the [`Params`] have been customized with.
+/// - [`errors::Error::InvalidMemoryLen`] if `memory` is shorter than
+///   [`Params::memory_required`].
+pub fn scrypt_with_memory(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    memory: &mut [u8],
+) -> Result<(), errors::Error> {
+    check_output_len(params, output)?;
+
+    // The checks in the ScryptParams constructor guarantee
+    // that the following is safe:
+    let n = 1 << params.log_n;
+    let r128 = (params.r as usize) * 128;
+    let pr128 = (params.p as usize) * r128;
+    let nr128 = n * r128;
+
+    let memory = memory
+        .get_mut(..params.memory_required())
+        .ok_or(errors::Error::InvalidMemoryLen)?;
+    let (b, rest) = memory.split_at_mut(pr128);
+    let (v, t) = rest.split_at_mut(nr128);
+
+    pbkdf2_hmac::<Sha256>(password, salt, 1, b);
 
     b.chunks_mut(r128).for_each(|chunk| {
-        romix::scrypt_ro_mix(chunk, &mut v, &mut t, n);
+        romix::scrypt_ro_mix(chunk, v, t, n);
     });
+
+    pbkdf2_hmac::<Sha256>(password, b, 1, output);
+
+    #[cfg(feature = "zeroize")]
+    memory.zeroize();
+
+    Ok(())
 }
 
-#[cfg(feature = "parallel")]
-fn romix_parallel(nr128: usize, r128: usize, n: usize, b: &mut [u8]) {
-    use rayon::{iter::ParallelIterator as _, slice::ParallelSliceMut as _};
+/// Check the output length required by scrypt:
+/// `output.len() > 0 && output.len() <= (2^32 - 1) * 32`, and that it matches the output length of
+/// `params` if one has been set.
+#[cfg_attr(not(feature = "password-hash"), allow(unused_variables))]
+fn check_output_len(params: &Params, output: &[u8]) -> Result<(), errors::InvalidOutputLen> {
+    if output.is_empty() || output.len() / 32 > 0xffff_ffff {
+        return Err(errors::InvalidOutputLen);
+    }
+
+    #[cfg(feature = "password-hash")]
+    if params.len.is_some_and(|len| len != output.len()) {
+        return Err(errors::InvalidOutputLen);
+    }
 
-    b.par_chunks_mut(r128).for_each(|chunk| {
-        let mut v = vec![0u8; nr128];
-        let mut t = vec![0u8; r128];
-        romix::scrypt_ro_mix(chunk, &mut v, &mut t, n);
-    });
+    Ok(())
 }
 
 /// scrypt password hashing type which can produce and verify strings in either the Password Hashing
@@ -161,6 +261,12 @@ fn romix_parallel(nr128: usize, r128: usize, n: usize, b: &mut [u8]) {
 pub struct Scrypt {
     /// Default parameters to use.
     params: Params,
+
+    /// Parallelism used to compute the `p` lanes.
+    parallelism: Parallelism,
+
+    /// Limits for params selected by input
+    verifier_limits: VerifierLimits,
 }
 
 #[cfg(any(feature = "kdf", feature = "mcf", feature = "phc"))]
@@ -168,15 +274,48 @@ impl Scrypt {
     /// Initialize [`Scrypt`] with default parameters.
     #[must_use]
     pub const fn new() -> Self {
-        Self {
-            params: Params::RECOMMENDED,
-        }
+        Self::new_with_params(Params::RECOMMENDED)
     }
 
     /// Initialize [`Scrypt`] with the provided parameters.
     #[must_use]
     pub const fn new_with_params(params: Params) -> Self {
-        Self { params }
+        Self {
+            params,
+            parallelism: Parallelism::DEFAULT,
+            verifier_limits: VerifierLimits::NONE,
+        }
+    }
+
+    /// Set the [`Parallelism`] used to compute the `p` lanes of password hashes.
+    #[must_use]
+    pub const fn with_parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Get the [`Parallelism`] used to compute the `p` lanes of password hashes.
+    #[must_use]
+    pub const fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    /// Set the [`VerifierLimits`] on the params this context accepts when they are selected by
+    /// its input, e.g. the params of a PHC or MCF string hash being verified, or those passed to
+    /// `hash_password_customized`.
+    ///
+    /// Hashes whose params exceed the limits are rejected with
+    /// [`password_hash::Error::ParamsInvalid`] before any memory is allocated.
+    #[must_use]
+    pub const fn with_verifier_limits(mut self, limits: VerifierLimits) -> Self {
+        self.verifier_limits = limits;
+        self
+    }
+
+    /// Get the configured [`VerifierLimits`].
+    #[must_use]
+    pub const fn verifier_limits(&self) -> VerifierLimits {
+        self.verifier_limits
     }
 }
 
@@ -190,7 +329,7 @@ impl From<Params> for Scrypt {
 #[cfg(feature = "kdf")]
 impl Kdf for Scrypt {
     fn derive_key(&self, password: &[u8], salt: &[u8], out: &mut [u8]) -> kdf::Result<()> {
-        scrypt(password, salt, &self.params, out)?;
+        scrypt_with_parallelism(password, salt, &self.params, out, self.parallelism)?;
         Ok(())
     }
 }
@@ -2,10 +2,18 @@
 //! strings which begin with `$7$`:
 //!
 //! <https://man.archlinux.org/man/crypt.5#scrypt>
+//!
+//! The grammar is the one used by libxcrypt: `$7$`, followed by `log_n` as a single Base64
+//! character, `r` and `p` as 5-character (30-bit) Base64 values, the salt, and finally `$` and
+//! the Base64-encoded hash. Unlike yescrypt's `$y$` hashes, `$7$` hashes have no flags field.
+//!
+//! libxcrypt uses the Base64 salt characters as the scrypt salt as-is, so they need not decode
+//! to whole bytes: [`Scrypt::hash_password_with_setting`] can be used to hash with the params and
+//! salt of an existing hash or setting string.
 
 pub use mcf::{PasswordHash, PasswordHashRef};
 
-use crate::{Params, Scrypt, scrypt};
+use crate::{Params, Scrypt, phc::Output, scrypt_with_parallelism};
 use alloc::{string::String, vec};
 use core::str;
 use mcf::Base64;
@@ -24,7 +32,7 @@ const SCRYPT_BASE64: Base64 = Base64::Crypt;
 const ENCODED_U32_LEN: usize = 5;
 
 /// Length of scrypt's params when encoded as binary: `log_n`: 1-byte, `r`/`p`: 5-bytes
-const PARAMS_LEN: usize = 1 + (2 * ENCODED_U32_LEN);
+pub(crate) const PARAMS_LEN: usize = 1 + (2 * ENCODED_U32_LEN);
 
 impl CustomizedPasswordHasher<PasswordHash> for Scrypt {
     type Params = Params;
@@ -37,9 +45,6 @@ impl CustomizedPasswordHasher<PasswordHash> for Scrypt {
         version: Option<Version>,
         params: Params,
     ) -> Result<PasswordHash> {
-        // TODO(tarcieri): tunable hash output size?
-        const HASH_SIZE: usize = 32;
-
         match alg_id {
             Some(MCF_ID) | None => (),
             _ => return Err(Error::Algorithm),
@@ -49,32 +54,101 @@ impl CustomizedPasswordHasher<PasswordHash> for Scrypt {
             return Err(Error::Version);
         }
 
+        self.verifier_limits.check(&params)?;
+        self.hash_password_mcf(password, salt, params)
+    }
+}
+
+impl PasswordHasher<PasswordHash> for Scrypt {
+    fn hash_password_with_salt(&self, password: &[u8], salt: &[u8]) -> Result<PasswordHash> {
+        self.hash_password_mcf(password, salt, self.params)
+    }
+}
+
+impl Scrypt {
+    /// Compute an MCF string hash using the given params.
+    fn hash_password_mcf(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        params: Params,
+    ) -> Result<PasswordHash> {
+        if !params.is_mcf_encodable() {
+            return Err(Error::ParamsInvalid);
+        }
+
         let params_and_salt = encode_params_and_salt(params, salt)?;
+        let len = params.len.unwrap_or(Params::RECOMMENDED_LEN);
+        self.hash_password_mcf_encoded(password, &params_and_salt, params, len)
+    }
 
+    /// Compute an MCF string hash from its already encoded params and salt.
+    fn hash_password_mcf_encoded(
+        &self,
+        password: &[u8],
+        params_and_salt: &str,
+        params: Params,
+        len: usize,
+    ) -> Result<PasswordHash> {
         // When used with MCF, the scrypt salt is Base64 encoded
         let salt = &params_and_salt.as_bytes()[PARAMS_LEN..];
 
-        let mut out = [0u8; HASH_SIZE];
-        scrypt(password, salt, &params, &mut out).map_err(|_| Error::OutputSize)?;
+        let mut buffer = [0u8; Output::MAX_LENGTH];
+        let out = buffer.get_mut(..len).ok_or(Error::OutputSize)?;
+        scrypt_with_parallelism(password, salt, &params, out, self.parallelism)
+            .map_err(|_| Error::OutputSize)?;
 
         // Begin building the Modular Crypt Format hash.
         let mut mcf_hash = PasswordHash::from_id(MCF_ID).expect("should be valid");
 
         // Add salt
         mcf_hash
-            .push_str(&params_and_salt)
+            .push_str(params_and_salt)
             .map_err(|_| Error::EncodingInvalid)?;
 
         // Add scrypt password hashing function output
-        mcf_hash.push_base64(&out, SCRYPT_BASE64);
+        mcf_hash.push_base64(out, SCRYPT_BASE64);
 
         Ok(mcf_hash)
     }
-}
 
-impl PasswordHasher<PasswordHash> for Scrypt {
-    fn hash_password_with_salt(&self, password: &[u8], salt: &[u8]) -> Result<PasswordHash> {
-        self.hash_password_customized(password, salt, None, None, self.params)
+    /// Compute an MCF string hash using the params and salt of the given `$7$` hash or setting
+    /// string, as libxcrypt's `crypt` function does.
+    ///
+    /// The `setting` may be a full hash, in which case the output has the same length as its
+    /// hash, or only the `$7$` prefix, params and salt, optionally followed by `$`, in which case
+    /// the output length is [`Params::RECOMMENDED_LEN`]. The salt is used exactly as encoded, so
+    /// any hash computed by libxcrypt can be recomputed even if its salt is not a whole number of
+    /// Base64-encoded bytes.
+    ///
+    /// # Errors
+    /// - [`Error::Algorithm`] if `setting` does not begin with `$7$`
+    /// - [`Error::EncodingInvalid`] if `setting` is malformed
+    /// - [`Error::ParamsInvalid`] if the params are invalid or exceed the verifier limits
+    pub fn hash_password_with_setting(
+        &self,
+        password: &[u8],
+        setting: &str,
+    ) -> Result<PasswordHash> {
+        let rest = setting.strip_prefix("$7$").ok_or(Error::Algorithm)?;
+
+        let (params_and_salt, hash) = match rest.split_once('$') {
+            Some((params_and_salt, hash)) => (params_and_salt, Some(hash)),
+            None => (rest, None),
+        };
+
+        let (params, _) = decode_params_and_salt(params_and_salt)?;
+        self.verifier_limits.check(&params)?;
+
+        let len = match hash {
+            Some(hash) if !hash.is_empty() => SCRYPT_BASE64
+                .decode_vec(hash)
+                .map_err(|_| Error::EncodingInvalid)?
+                .len(),
+            _ => Params::RECOMMENDED_LEN,
+        };
+
+        self.hash_password_mcf_encoded(password, params_and_salt, params, len)
     }
 }
 
@@ -96,6 +170,7 @@ impl PasswordVerifier<PasswordHashRef> for Scrypt {
         // decode params and salt
         let (params, salt) =
             decode_params_and_salt(fields.next().ok_or(Error::EncodingInvalid)?.as_str())?;
+        self.verifier_limits.check(&params)?;
 
         // decode expected password hash
         let expected = fields
@@ -109,8 +184,10 @@ impl PasswordVerifier<PasswordHashRef> for Scrypt {
             return Err(Error::EncodingInvalid);
         }
 
+        // The output length is inferred from the stored hash
         let mut actual = vec![0u8; expected.len()];
-        scrypt(password, salt, &params, &mut actual).map_err(|_| Error::OutputSize)?;
+        scrypt_with_parallelism(password, salt, &params, &mut actual, self.parallelism)
+            .map_err(|_| Error::OutputSize)?;
 
         if ctutils::CtEq::ct_ne(actual.as_slice(), &expected).into() {
             return Err(Error::PasswordInvalid);
@@ -135,7 +212,7 @@ static ATOI64: [u8; 128] = {
 };
 
 /// Decode scrypt parameters and salt from the combined string they're encoded in.
-fn decode_params_and_salt(s: &str) -> Result<(Params, &[u8])> {
+pub(crate) fn decode_params_and_salt(s: &str) -> Result<(Params, &[u8])> {
     let bytes = s.as_bytes();
 
     if bytes.is_empty() {
@@ -159,7 +236,18 @@ fn decode_params_and_salt(s: &str) -> Result<(Params, &[u8])> {
 
     let params = Params::new(log_n, r, p).map_err(|_| Error::ParamsInvalid)?;
 
-    Ok((params, &s.as_bytes()[pos..]))
+    if !params.is_mcf_encodable() {
+        return Err(Error::ParamsInvalid);
+    }
+
+    // salt, which is used as-is but must consist of Base64 characters
+    let salt = &bytes[pos..];
+
+    if !salt.iter().all(|b| ITOA64.contains(b)) {
+        return Err(Error::EncodingInvalid);
+    }
+
+    Ok((params, salt))
 }
 
 /// Encode scrypt parameters and salt into scrypt-flavored Base64.
@@ -173,7 +261,7 @@ fn encode_params_and_salt(params: Params, salt: &[u8]) -> Result<String> {
 }
 
 /// Encode params as scrypt-flavored Base64 to the given output buffer.
-fn encode_params(params: Params, out: &mut [u8]) -> Result<&str> {
+pub(crate) fn encode_params(params: Params, out: &mut [u8]) -> Result<&str> {
     // encode log_n (uses a special 1-byte encoding)
     let encoded_log_n = *ITOA64
         .get(params.log_n as usize)
@@ -235,6 +323,7 @@ mod tests {
         CustomizedPasswordHasher, Error, Params, PasswordHash, PasswordHashRef, PasswordVerifier,
         SCRYPT_BASE64, Scrypt, decode_params_and_salt,
     };
+    use crate::VerifierLimits;
 
     /// Password used to make the example MCF hash.
     const EXAMPLE_PASSWORD: &[u8] = b"pleaseletmein";
@@ -281,6 +370,22 @@ mod tests {
         assert_eq!(expected_hash, actual_hash);
     }
 
+    #[test]
+    fn hash_password_with_output_len() {
+        let params = Params::new_with_output_len(EXAMPLE_LOG_N, EXAMPLE_R, EXAMPLE_P, 64).unwrap();
+        let hash: PasswordHash = Scrypt::new()
+            .hash_password_with_params(EXAMPLE_PASSWORD, EXAMPLE_SALT.as_bytes(), params)
+            .unwrap();
+
+        let output = hash.fields().nth(1).unwrap().decode_base64(SCRYPT_BASE64);
+        assert_eq!(output.unwrap().len(), 64);
+
+        assert_eq!(
+            Scrypt::new().verify_password(EXAMPLE_PASSWORD, &hash),
+            Ok(())
+        );
+    }
+
     #[test]
     fn verify_password() {
         let hash = PasswordHashRef::new(EXAMPLE_MCF_HASH).unwrap();
@@ -294,4 +399,83 @@ mod tests {
             Err(Error::PasswordInvalid)
         );
     }
+
+    #[test]
+    fn verify_password_exceeding_limits() {
+        let limits = VerifierLimits {
+            max_log_n: EXAMPLE_LOG_N,
+            ..VerifierLimits::default()
+        };
+        let verifier = Scrypt::new().with_verifier_limits(limits);
+
+        let hash = PasswordHashRef::new(EXAMPLE_MCF_HASH).unwrap();
+        assert_eq!(verifier.verify_password(EXAMPLE_PASSWORD, hash), Ok(()));
+
+        // 2 TiB of memory
+        let hash = PasswordHashRef::new(
+            "$7$W/..../....Mq4YHD2syxYT.MsH1Ek0n1$JyHIxez0DOwm0r6.kAIohc8UFBOLU4xX8a1wGBpLrw7",
+        )
+        .unwrap();
+        assert_eq!(
+            verifier.verify_password(EXAMPLE_PASSWORD, hash),
+            Err(Error::ParamsInvalid)
+        );
+    }
+
+    /// Generated using libxcrypt's `crypt` with settings containing a 5-character salt, an empty
+    /// salt, and the salt of [`EXAMPLE_MCF_HASH`].
+    const LIBXCRYPT_HASHES: &[&str] = &[
+        "$7$2/....1....abcde$3tGpgPX5Yg/R.Hdw1VtdiD/TTq2lJl4/VVi6oOQY.V/",
+        "$7$2/....1....$TY.pmu/94vwDuXnl9MEe.UBJMjB.fAExK3aMirSgv21",
+        "$7$4/..../....Mq4YHD2syxYT.MsH1Ek0n1$rMBE5uosh7SqhdfcLrWaIPiDUiXaYrCU3VR9lsOKEL1",
+    ];
+
+    #[test]
+    fn hash_password_with_setting() {
+        let scrypt = Scrypt::new();
+
+        for &expected in LIBXCRYPT_HASHES {
+            let (setting, _) = expected.rsplit_once('$').unwrap();
+
+            for setting in [setting, &expected[..=setting.len()], expected] {
+                let hash = scrypt
+                    .hash_password_with_setting(EXAMPLE_PASSWORD, setting)
+                    .unwrap();
+                assert_eq!(hash.as_str(), expected);
+            }
+
+            let hash = PasswordHashRef::new(expected).unwrap();
+            assert_eq!(scrypt.verify_password(EXAMPLE_PASSWORD, hash), Ok(()));
+        }
+
+        assert_eq!(
+            scrypt.hash_password_with_setting(EXAMPLE_PASSWORD, "$y$2/....1....abcde"),
+            Err(Error::Algorithm)
+        );
+    }
+
+    #[test]
+    fn reject_non_mcf_encodable() {
+        let params = Params::new(0, 1, 1).unwrap();
+        assert!(!params.is_mcf_encodable());
+        assert!(Params::RECOMMENDED.is_mcf_encodable());
+
+        let result: Result<PasswordHash, Error> =
+            Scrypt::new().hash_password_with_params(EXAMPLE_PASSWORD, b"salt", params);
+        assert_eq!(result, Err(Error::ParamsInvalid));
+
+        // rejected by libxcrypt
+        assert_eq!(
+            decode_params_and_salt("./..../....ab"),
+            Err(Error::ParamsInvalid)
+        );
+        assert_eq!(
+            decode_params_and_salt("4....z....zab"),
+            Err(Error::ParamsInvalid)
+        );
+        assert_eq!(
+            decode_params_and_salt("4/..../....a!b"),
+            Err(Error::EncodingInvalid)
+        );
+    }
 }
@@ -0,0 +1,319 @@
+//! Parallel computation of the scrypt `p` lanes.
+
+use crate::{Params, romix};
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Controls how many threads are used to compute the `p` independent lanes of scrypt, and how much
+/// memory they may use.
+///
+/// Each lane being computed needs its own `N * r * 128` byte working buffer, so computing every
+/// lane of a `p = 8` hash on its own thread needs 8 times the memory of computing them one after
+/// the other. Lanes are evenly split between at most [`Parallelism::threads`] threads, and the
+/// number of threads is reduced so that the total working memory stays within
+/// [`Parallelism::max_memory`], down to processing all lanes on the calling thread.
+///
+/// Threads are spawned using [rayon](https://docs.rs/rayon) if the `parallel` feature is enabled,
+/// or else using scoped threads from the standard library if the `std` feature is enabled. When
+/// neither feature is enabled, all lanes are processed on the calling thread.
+///
+/// On CPUs with AVX2, each thread can compute two of its lanes at a time, which is faster but needs
+/// a second working buffer per thread. This must be enabled with [`Parallelism::with_interleave`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Parallelism {
+    /// Maximum number of threads
+    threads: usize,
+
+    /// Maximum working memory in bytes
+    max_memory: usize,
+
+    /// Whether threads may compute two lanes at a time
+    interleave: bool,
+}
+
+impl Parallelism {
+    /// Process all lanes on the calling thread.
+    pub const SEQUENTIAL: Self = Self::new(1);
+
+    /// Process each lane on its own thread, without a memory limit.
+    ///
+    /// This is the default when the `parallel` feature is enabled.
+    pub const UNBOUNDED: Self = Self::new(usize::MAX);
+
+    /// Default: [`Parallelism::UNBOUNDED`] if the `parallel` feature is enabled, or else
+    /// [`Parallelism::SEQUENTIAL`].
+    pub(crate) const DEFAULT: Self = if cfg!(feature = "parallel") {
+        Self::UNBOUNDED
+    } else {
+        Self::SEQUENTIAL
+    };
+
+    /// Use up to `threads` threads, without a memory limit.
+    ///
+    /// A `threads` value of `0` is treated as `1`.
+    #[must_use]
+    pub const fn new(threads: usize) -> Self {
+        Self {
+            threads: if threads == 0 { 1 } else { threads },
+            max_memory: usize::MAX,
+            interleave: false,
+        }
+    }
+
+    /// Limit the total working memory to `max_memory` bytes, reducing the number of threads used
+    /// when it would be exceeded.
+    ///
+    /// The limit only bounds parallelism: hashes whose [`Params::memory_required`] exceeds it are
+    /// still computed sequentially.
+    #[must_use]
+    pub const fn with_max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// Allow each thread to compute two of its lanes at a time when that is faster, doubling the
+    /// working memory used by each thread.
+    ///
+    /// Lanes are only interleaved if the doubled working memory stays within
+    /// [`Parallelism::max_memory`].
+    #[must_use]
+    pub const fn with_interleave(mut self, interleave: bool) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+    /// Maximum number of threads.
+    #[must_use]
+    pub const fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Maximum working memory in bytes.
+    #[must_use]
+    pub const fn max_memory(&self) -> usize {
+        self.max_memory
+    }
+
+    /// Whether each thread may compute two lanes at a time.
+    #[must_use]
+    pub const fn interleave(&self) -> bool {
+        self.interleave
+    }
+
+    /// Plan how to compute the lanes for the given params: use at most one thread per lane, and
+    /// as many threads as fit within the memory limit. If enabled and memory allows, each thread
+    /// computes two lanes at a time when that is faster.
+    pub(crate) fn plan(&self, params: &Params) -> Plan {
+        let p = params.p as usize;
+        let r128 = (params.r as usize) * 128;
+        let nr128 = (1usize << params.log_n) * r128;
+        let pr128 = p * r128;
+
+        // Each lane being computed has its own `v` and `t` buffers, while `b` is shared
+        let per_lane = nr128.saturating_add(r128);
+        let lane_buffers = self.max_memory.saturating_sub(pr128) / per_lane;
+
+        let threads = if cfg!(any(feature = "parallel", feature = "std")) {
+            self.threads.min(p).min(lane_buffers).max(1)
+        } else {
+            1
+        };
+
+        let interleave = self.interleave
+            && crate::block_mix::x2_available()
+            && p.div_ceil(threads) >= 2
+            && threads.saturating_mul(2) <= lane_buffers;
+
+        Plan {
+            threads,
+            interleave,
+        }
+    }
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// How the lanes of a hash are computed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Plan {
+    /// Number of threads
+    pub(crate) threads: usize,
+
+    /// Whether each thread computes two lanes at a time
+    pub(crate) interleave: bool,
+}
+
+/// Execute `ROMix` on each of the lanes in `b`, as planned.
+pub(crate) fn romix_lanes(b: &mut [u8], params: &Params, plan: Plan) {
+    let r128 = (params.r as usize) * 128;
+    let nr128 = (1 << params.log_n) * r128;
+    let lanes_at_once = if plan.interleave { 2 } else { 1 };
+
+    let romix_thread = |lanes: &mut [u8]| {
+        let mut memory = vec![0u8; lanes_at_once * (nr128 + r128)];
+        romix_thread_with_memory(lanes, params, plan.interleave, &mut memory);
+    };
+
+    #[cfg(any(feature = "parallel", feature = "std"))]
+    if plan.threads > 1 {
+        let lanes_per_thread = (params.p as usize).div_ceil(plan.threads);
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::{iter::ParallelIterator as _, slice::ParallelSliceMut as _};
+
+            b.par_chunks_mut(lanes_per_thread * r128)
+                .for_each(romix_thread);
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        std::thread::scope(|scope| {
+            for lanes in b.chunks_mut(lanes_per_thread * r128) {
+                scope.spawn(move || romix_thread(lanes));
+            }
+        });
+
+        return;
+    }
+
+    romix_thread(b);
+}
+
+/// Execute `ROMix` on each of the `lanes` computed by a single thread, taking the `v` and `t`
+/// buffers of one lane, or two if `interleave` is set, from `memory`.
+///
+/// When the `zeroize` feature is enabled, `memory` is zeroized before returning.
+fn romix_thread_with_memory(
+    lanes: &mut [u8],
+    params: &Params,
+    interleave: bool,
+    memory: &mut [u8],
+) {
+    let n = 1 << params.log_n;
+    let r128 = (params.r as usize) * 128;
+    let nr128 = n * r128;
+
+    if interleave {
+        let (v, t) = memory.split_at_mut(2 * nr128);
+        let (v0, v1) = v.split_at_mut(nr128);
+        let (t0, t1) = t.split_at_mut(r128);
+
+        for pair in lanes.chunks_mut(2 * r128) {
+            if pair.len() == 2 * r128 {
+                let (b0, b1) = pair.split_at_mut(r128);
+                romix::scrypt_ro_mix_x2([b0, b1], [&mut *v0, &mut *v1], [&mut *t0, &mut *t1], n);
+            } else {
+                romix::scrypt_ro_mix(pair, v0, t0, n);
+            }
+        }
+    } else {
+        let (v, t) = memory.split_at_mut(nr128);
+
+        lanes.chunks_mut(r128).for_each(|chunk| {
+            romix::scrypt_ro_mix(chunk, v, t, n);
+        });
+    }
+
+    #[cfg(feature = "zeroize")]
+    memory.zeroize();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Parallelism, Plan};
+    use crate::{Params, block_mix::x2_available};
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn romix_thread_zeroize() {
+        use alloc::vec::Vec;
+
+        let params = Params::new(4, 8, 3).unwrap();
+        let r128 = 8 * 128;
+        let nr128 = 16 * r128;
+        let input: Vec<u8> = (0..3 * r128)
+            .map(|i| u8::try_from(i % 251).unwrap())
+            .collect();
+
+        let mut expected = input.clone();
+        let mut memory = vec![0xaau8; nr128 + r128];
+        super::romix_thread_with_memory(&mut expected, &params, false, &mut memory);
+        assert!(memory.iter().all(|&byte| byte == 0));
+
+        let mut lanes = input.clone();
+        let mut memory = vec![0xaau8; 2 * (nr128 + r128)];
+        super::romix_thread_with_memory(&mut lanes, &params, true, &mut memory);
+        assert!(memory.iter().all(|&byte| byte == 0));
+        assert_eq!(lanes, expected);
+        assert_ne!(lanes, input);
+    }
+
+    #[test]
+    fn plan() {
+        let params = Params::new(10, 8, 16).unwrap();
+        let per_lane = (1024 + 1) * 8 * 128;
+        let shared = 16 * 8 * 128;
+        let threaded = cfg!(any(feature = "parallel", feature = "std"));
+
+        let plan = |parallelism: Parallelism| parallelism.plan(&params);
+
+        assert_eq!(
+            plan(Parallelism::SEQUENTIAL),
+            Plan {
+                threads: 1,
+                interleave: false,
+            }
+        );
+
+        // Interleaving is opt-in
+        assert_eq!(
+            plan(Parallelism::SEQUENTIAL.with_interleave(true)),
+            Plan {
+                threads: 1,
+                interleave: x2_available(),
+            }
+        );
+
+        // ...and subject to the memory cap
+        let parallelism = Parallelism::SEQUENTIAL
+            .with_interleave(true)
+            .with_max_memory(shared + per_lane);
+        assert_eq!(
+            plan(parallelism),
+            Plan {
+                threads: 1,
+                interleave: false,
+            }
+        );
+
+        if threaded {
+            assert_eq!(plan(Parallelism::UNBOUNDED).threads, 16);
+            assert_eq!(plan(Parallelism::new(4)).threads, 4);
+
+            // Memory cap reduces the number of threads...
+            let parallelism = Parallelism::new(4).with_max_memory(shared + 3 * per_lane);
+            assert_eq!(
+                plan(parallelism),
+                Plan {
+                    threads: 3,
+                    interleave: false,
+                }
+            );
+        }
+
+        // ...down to a single thread, even if a single lane exceeds the cap
+        let parallelism = Parallelism::new(4).with_max_memory(shared);
+        assert_eq!(
+            plan(parallelism),
+            Plan {
+                threads: 1,
+                interleave: false,
+            }
+        );
+    }
+}
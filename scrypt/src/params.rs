@@ -1,4 +1,4 @@
-use crate::errors::InvalidParams;
+use crate::errors::{self, InvalidParams};
 
 #[cfg(feature = "phc")]
 use password_hash::{Error, phc};
@@ -6,6 +6,11 @@ use password_hash::{Error, phc};
 #[cfg(all(feature = "phc", doc))]
 use password_hash::PasswordHasher;
 
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
 /// The Scrypt parameter values.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Params {
@@ -96,7 +101,7 @@ impl Params {
         // check: p <= ((2^32-1) * 32) / (128 * r)
         // It takes a bit of re-arranging to get the check above into this form,
         // but it is indeed the same.
-        if r * p >= 0x4000_0000 {
+        if u64::from(r) * u64::from(p) >= 0x4000_0000 {
             return Err(InvalidParams);
         }
 
@@ -111,9 +116,10 @@ impl Params {
 
     /// Create a new instance of [`Params`], overriding the output length.
     ///
-    /// Note that this length is only intended for use with the [`PasswordHasher`] API, and not with
-    /// the low-level [`scrypt::scrypt`][`crate::scrypt`] API, which determines the output length
-    /// using the size of the `output` slice.
+    /// This length determines the size of the hashes computed using the [`PasswordHasher`] API,
+    /// for both PHC and MCF hashes. The low-level [`scrypt::scrypt`][`crate::scrypt`] API
+    /// determines the output length using the size of the `output` slice, and returns an error if
+    /// it differs from `len`.
     ///
     /// The allowed values for `len` are between 10 bytes (80 bits) and 64 bytes inclusive.
     /// These lengths come from the [PHC string format specification](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md)
@@ -137,6 +143,59 @@ impl Params {
         Ok(ret)
     }
 
+    /// Pick params which can be computed on the running host within `max_time`, using at most
+    /// `max_memory` bytes for the `N * r * 128` byte working buffer of each lane.
+    ///
+    /// This is equivalent to the `pickparams` function of the reference implementation: it
+    /// benchmarks Salsa20/8 throughput, fixes `r = 8`, then chooses `N` as large as the time and
+    /// memory limits allow. If the time limit allows for more computation than fits in memory, `p`
+    /// is increased to use up the remaining time. A `max_memory` below 1 MiB is treated as 1 MiB.
+    ///
+    /// The total memory required is given by [`Params::memory_required`], which includes an
+    /// additional `(p + 1) * r * 128` bytes.
+    #[cfg(feature = "std")]
+    #[allow(clippy::missing_panics_doc, reason = "picked params are always valid")]
+    #[must_use]
+    pub fn pick(max_memory: usize, max_time: Duration) -> Params {
+        const R: u32 = 8;
+
+        let max_memory = u64::try_from(max_memory.max(1 << 20)).unwrap_or(u64::MAX);
+
+        // Number of Salsa20/8 core operations allowed, with a minimum of 2^15
+        let (ops, elapsed) = salsa20_8_throughput();
+        let max_ops = (u128::from(ops) * max_time.as_nanos() / elapsed.as_nanos().max(1))
+            .try_into()
+            .unwrap_or(u64::MAX)
+            .max(1 << 15);
+
+        // Choose the largest `N = 2^log_n` such that `N / 2 < max_n`
+        let log_n_for = |max_n: u64| {
+            (1..63)
+                .find(|&log_n| 1u64 << log_n > max_n / 2)
+                .unwrap_or(63)
+        };
+
+        // The memory limit requires `N * r * 128 <= max_memory`, while the time limit requires
+        // `4 * N * r * p <= max_ops`: if `max_ops < max_memory / 32`, the time limit imposes the
+        // stronger limit on `N`.
+        let (log_n, p) = if max_ops < max_memory / 32 {
+            (log_n_for(max_ops / (u64::from(R) * 4)), 1)
+        } else {
+            let log_n = log_n_for(max_memory / (u64::from(R) * 128));
+
+            // Choose `p` based on the time limit, within the limits of `Params::new`
+            let max_rp = ((max_ops / 4) >> log_n)
+                .min(0x3fff_ffff)
+                .min(u64::try_from(usize::MAX / 128).unwrap_or(u64::MAX));
+            let p = u32::try_from(max_rp).unwrap_or(u32::MAX) / R;
+
+            (log_n, p.max(1))
+        };
+
+        // `N * r * 128` is at most `max_memory`, `p * r * 128` fits in a `usize` and `p * r < 2^30`
+        Params::new(log_n, R, p).expect("picked params are valid")
+    }
+
     /// Deprecated: recommended values according to the OWASP cheat sheet.
     #[deprecated(since = "0.12.0", note = "use Params::RECOMMENDED instead")]
     #[must_use]
@@ -176,6 +235,33 @@ impl Params {
     pub const fn p(&self) -> u32 {
         self.p
     }
+
+    /// Size in bytes of the working memory required to compute scrypt with these parameters
+    /// using [`scrypt_with_memory`][`crate::scrypt_with_memory`].
+    ///
+    /// This is `(N + p + 1) * r * 128` bytes, saturating at `usize::MAX`.
+    #[must_use]
+    pub const fn memory_required(&self) -> usize {
+        // The checks in the `Params` constructor guarantee that `N * r * 128` and
+        // `p * r * 128` don't overflow individually.
+        let r128 = (self.r as usize) * 128;
+        let nr128 = (1usize << self.log_n) * r128;
+        let pr128 = (self.p as usize) * r128;
+
+        nr128.saturating_add(pr128).saturating_add(r128)
+    }
+
+    /// Whether these parameters can be encoded in a `$7$` Modular Crypt Format hash.
+    ///
+    /// The format encodes `log_n` as a single Base64 character and `r` and `p` as 30-bit values,
+    /// which [`Params::new`] already guarantees: the only additional restriction is that
+    /// libxcrypt rejects hashes with `log_n = 0`. Hashing to MCF with parameters for which this
+    /// returns `false` fails with [`password_hash::Error::ParamsInvalid`].
+    #[cfg(feature = "mcf")]
+    #[must_use]
+    pub const fn is_mcf_encodable(&self) -> bool {
+        self.log_n >= 1
+    }
 }
 
 impl Default for Params {
@@ -184,6 +270,87 @@ impl Default for Params {
     }
 }
 
+/// Measure the throughput of the Salsa20/8 core, returning a number of operations and the time
+/// taken to compute them.
+#[cfg(feature = "std")]
+fn salsa20_8_throughput() -> (u64, Duration) {
+    /// Minimum time to spend measuring.
+    const MIN_TIME: Duration = Duration::from_millis(50);
+
+    /// `N` used for each `ROMix` call, with `r = 1`.
+    const N: usize = 128;
+
+    let mut b = [0u8; 128];
+    let mut v = [0u8; N * 128];
+    let mut t = [0u8; 128];
+
+    let start = Instant::now();
+    let mut ops = 0u64;
+
+    loop {
+        // Each `ROMix` call computes `2 * N` `BlockMix` operations, each of which computes
+        // `2 * r` Salsa20/8 cores
+        crate::romix::scrypt_ro_mix(&mut b, &mut v, &mut t, N);
+        ops += 4 * N as u64;
+
+        let elapsed = start.elapsed();
+        if elapsed >= MIN_TIME {
+            return (ops, elapsed);
+        }
+    }
+}
+
+/// Upper bounds on the [`Params`] a [`Scrypt`][`crate::Scrypt`] verifier accepts when they are
+/// selected by its input, e.g. when verifying a PHC or MCF string hash.
+///
+/// Without limits, a hash such as `$7$W/..../....` makes the verifier allocate 2 TiB of memory
+/// and run for an unbounded amount of time. If an attacker can influence stored hashes, limits
+/// should be set to the highest parameters legitimately in use.
+///
+/// By default no limits are imposed beyond those of [`Params`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct VerifierLimits {
+    /// Maximum working memory in bytes, as computed by [`Params::memory_required`].
+    pub max_memory: usize,
+
+    /// Maximum log₂ of the Scrypt parameter `N`.
+    pub max_log_n: u8,
+
+    /// Maximum Scrypt parameter `p`.
+    pub max_p: u32,
+}
+
+impl VerifierLimits {
+    /// No limits beyond those of [`Params`].
+    pub const NONE: Self = Self {
+        max_memory: usize::MAX,
+        max_log_n: u8::MAX,
+        max_p: u32::MAX,
+    };
+
+    /// Check the given [`Params`] against these limits.
+    ///
+    /// # Errors
+    /// Returns [`errors::Error::ParamsExceedLimits`] if the memory required by `params`, or its
+    /// `log_n` or `p`, exceeds its limit.
+    pub const fn check(&self, params: &Params) -> Result<(), errors::Error> {
+        if params.memory_required() > self.max_memory
+            || params.log_n > self.max_log_n
+            || params.p > self.max_p
+        {
+            return Err(errors::Error::ParamsExceedLimits);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for VerifierLimits {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
 #[cfg(feature = "phc")]
 impl TryFrom<&phc::ParamsString> for Params {
     type Error = Error;
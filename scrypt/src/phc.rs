@@ -2,7 +2,7 @@
 
 pub use password_hash::phc::{Ident, Output, PasswordHash, Salt};
 
-use crate::{Params, Scrypt, scrypt};
+use crate::{Params, Scrypt, scrypt_with_parallelism};
 use password_hash::{CustomizedPasswordHasher, Error, PasswordHasher, Result, Version};
 
 /// Algorithm name
@@ -32,12 +32,32 @@ impl CustomizedPasswordHasher<PasswordHash> for Scrypt {
             return Err(Error::Version);
         }
 
+        self.verifier_limits.check(&params)?;
+        self.hash_password_phc(password, salt, params)
+    }
+}
+
+impl PasswordHasher<PasswordHash> for Scrypt {
+    fn hash_password_with_salt(&self, password: &[u8], salt: &[u8]) -> Result<PasswordHash> {
+        self.hash_password_phc(password, salt, self.params)
+    }
+}
+
+impl Scrypt {
+    /// Compute a PHC string hash using the given params.
+    fn hash_password_phc(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        params: Params,
+    ) -> Result<PasswordHash> {
         let salt = Salt::new(salt)?;
         let len = params.len.unwrap_or(Params::RECOMMENDED_LEN);
 
         let mut buffer = [0u8; Output::MAX_LENGTH];
         let out = buffer.get_mut(..len).ok_or(Error::OutputSize)?;
-        scrypt(password, &salt, &params, out).map_err(|_| Error::OutputSize)?;
+        scrypt_with_parallelism(password, &salt, &params, out, self.parallelism)
+            .map_err(|_| Error::OutputSize)?;
         let output = Output::new(out)?;
 
         Ok(PasswordHash {
@@ -50,16 +70,11 @@ impl CustomizedPasswordHasher<PasswordHash> for Scrypt {
     }
 }
 
-impl PasswordHasher<PasswordHash> for Scrypt {
-    fn hash_password_with_salt(&self, password: &[u8], salt: &[u8]) -> Result<PasswordHash> {
-        self.hash_password_customized(password, salt, None, None, self.params)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::{PasswordHash, Scrypt};
-    use password_hash::PasswordVerifier;
+    use crate::VerifierLimits;
+    use password_hash::{Error, PasswordVerifier};
 
     /// Test vector from passlib:
     /// <https://passlib.readthedocs.io/en/stable/lib/passlib.hash.scrypt.html>
@@ -84,4 +99,18 @@ mod tests {
         let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
         assert!(Scrypt::new().verify_password(b"invalid", &hash).is_err());
     }
+
+    #[cfg(feature = "password-hash")]
+    #[test]
+    fn password_hash_reject_params_exceeding_limits() {
+        let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
+        let verifier = Scrypt::new().with_verifier_limits(VerifierLimits {
+            max_memory: 32 * 1024 * 1024,
+            ..VerifierLimits::default()
+        });
+        assert_eq!(
+            verifier.verify_password(b"password", &hash),
+            Err(Error::ParamsInvalid)
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use core::ops::Range;
+
 /// Execute the `ROMix` operation in-place.
 /// b - the data to operate on
 /// v - a temporary variable to store the vector V
@@ -5,38 +7,92 @@
 /// n - the scrypt parameter N
 #[allow(clippy::many_single_char_names)]
 pub(crate) fn scrypt_ro_mix(b: &mut [u8], v: &mut [u8], t: &mut [u8], n: usize) {
-    fn integerify(x: &[u8], n: usize) -> usize {
-        // n is a power of 2, so n - 1 gives us a bitmask that we can use to perform a calculation
-        // mod n using a simple bitwise and.
-        let mask = n - 1;
-        // This cast is safe since we're going to get the value mod n (which is a power of 2), so we
-        // don't have to care about truncating any of the high bits off
-        let t = u32::from_le_bytes(
-            x[x.len() - 64..x.len() - 60]
-                .try_into()
-                .expect("incorrect length"),
-        );
-
-        (t as usize) & mask
-    }
+    crate::block_mix::shuffle_in(b);
+    scrypt_ro_mix_fill(b, v, 0..n);
+    scrypt_ro_mix_mix(b, v, t, n, n);
+    crate::block_mix::shuffle_out(b);
+}
 
+/// Execute the given iterations of the first loop of `ROMix`, which fills the vector V.
+/// b - the shuffled data to operate on
+/// v - the vector V
+/// range - the indices of the blocks of V to compute
+pub(crate) fn scrypt_ro_mix_fill(b: &mut [u8], v: &mut [u8], range: Range<usize>) {
     let len = b.len();
 
-    crate::block_mix::shuffle_in(b);
-
-    for chunk in v.chunks_mut(len) {
+    for chunk in v[range.start * len..range.end * len].chunks_mut(len) {
         chunk.copy_from_slice(b);
         crate::block_mix::scrypt_block_mix(chunk, b);
     }
+}
 
-    for _ in 0..n {
+/// Execute `count` iterations of the second loop of `ROMix`, which reads from the vector V.
+/// b - the shuffled data to operate on
+/// v - the vector V
+/// t - a temporary variable to store the result of the xor
+/// n - the scrypt parameter N
+/// count - the number of iterations
+pub(crate) fn scrypt_ro_mix_mix(b: &mut [u8], v: &[u8], t: &mut [u8], n: usize, count: usize) {
+    let len = b.len();
+
+    for _ in 0..count {
         let j = integerify(b, n);
         xor(b, &v[j * len..(j + 1) * len], t);
 
         crate::block_mix::scrypt_block_mix(t, b);
     }
+}
 
-    crate::block_mix::shuffle_out(b);
+/// Execute the `ROMix` operation in-place on two lanes at once, using
+/// [`scrypt_block_mix_x2`][`crate::block_mix::scrypt_block_mix_x2`].
+/// b0, b1 - the data to operate on
+/// v0, v1 - temporary variables to store the vector V of each lane
+/// t0, t1 - temporary variables to store the result of the xor of each lane
+/// n - the scrypt parameter N
+#[cfg(feature = "alloc")]
+pub(crate) fn scrypt_ro_mix_x2(
+    [b0, b1]: [&mut [u8]; 2],
+    [v0, v1]: [&mut [u8]; 2],
+    [t0, t1]: [&mut [u8]; 2],
+    n: usize,
+) {
+    let len = b0.len();
+
+    crate::block_mix::shuffle_in(b0);
+    crate::block_mix::shuffle_in(b1);
+
+    for (chunk0, chunk1) in v0.chunks_mut(len).zip(v1.chunks_mut(len)) {
+        chunk0.copy_from_slice(b0);
+        chunk1.copy_from_slice(b1);
+        crate::block_mix::scrypt_block_mix_x2(chunk0, b0, chunk1, b1);
+    }
+
+    for _ in 0..n {
+        let j0 = integerify(b0, n);
+        let j1 = integerify(b1, n);
+        xor(b0, &v0[j0 * len..(j0 + 1) * len], t0);
+        xor(b1, &v1[j1 * len..(j1 + 1) * len], t1);
+
+        crate::block_mix::scrypt_block_mix_x2(t0, b0, t1, b1);
+    }
+
+    crate::block_mix::shuffle_out(b0);
+    crate::block_mix::shuffle_out(b1);
+}
+
+fn integerify(x: &[u8], n: usize) -> usize {
+    // n is a power of 2, so n - 1 gives us a bitmask that we can use to perform a calculation
+    // mod n using a simple bitwise and.
+    let mask = n - 1;
+    // This cast is safe since we're going to get the value mod n (which is a power of 2), so we
+    // don't have to care about truncating any of the high bits off
+    let t = u32::from_le_bytes(
+        x[x.len() - 64..x.len() - 60]
+            .try_into()
+            .expect("incorrect length"),
+    );
+
+    (t as usize) & mask
 }
 
 fn xor(x: &[u8], y: &[u8], output: &mut [u8]) {
@@ -1,6 +1,9 @@
 //! Integration tests.
 
-use scrypt::{Params, scrypt};
+use scrypt::{Params, VerifierLimits, errors, scrypt_with_memory};
+
+#[cfg(feature = "alloc")]
+use scrypt::{IncrementalScrypt, Parallelism, scrypt, scrypt_with_parallelism};
 
 struct Test {
     password: &'static str,
@@ -60,6 +63,7 @@ fn tests() -> Vec<Test> {
     ]
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_scrypt() {
     let tests = tests();
@@ -76,3 +80,200 @@ fn test_scrypt() {
         assert!(result == t.expected);
     }
 }
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_scrypt_with_parallelism() {
+    let tests = tests();
+    for t in tests.iter() {
+        let params = Params::new(t.log_n, t.r, t.p).unwrap();
+
+        for parallelism in [
+            Parallelism::SEQUENTIAL,
+            Parallelism::SEQUENTIAL.with_interleave(true),
+            Parallelism::new(3),
+            Parallelism::new(3).with_interleave(true),
+            Parallelism::UNBOUNDED,
+            Parallelism::UNBOUNDED.with_max_memory(params.memory_required()),
+        ] {
+            let mut result = vec![0u8; t.expected.len()];
+            scrypt_with_parallelism(
+                t.password.as_bytes(),
+                t.salt.as_bytes(),
+                &params,
+                &mut result,
+                parallelism,
+            )
+            .unwrap();
+            assert!(result == t.expected);
+        }
+    }
+}
+
+#[test]
+fn test_scrypt_with_memory() {
+    let tests = tests();
+    for t in tests.iter() {
+        let mut result = vec![0u8; t.expected.len()];
+        let params = Params::new(t.log_n, t.r, t.p).unwrap();
+
+        // Extra trailing memory is ignored
+        let mut memory = vec![0u8; params.memory_required() + 1];
+        scrypt_with_memory(
+            t.password.as_bytes(),
+            t.salt.as_bytes(),
+            &params,
+            &mut result,
+            &mut memory,
+        )
+        .unwrap();
+        assert!(result == t.expected);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_scrypt_with_memory_zeroize() {
+    let params = Params::new(4, 8, 2).unwrap();
+    let required = params.memory_required();
+
+    let mut result = [0u8; 32];
+    let mut memory = vec![0xaau8; required + 64];
+    scrypt_with_memory(b"password", b"salt", &params, &mut result, &mut memory).unwrap();
+    assert!(memory[..required].iter().all(|&byte| byte == 0));
+    assert!(memory[required..].iter().all(|&byte| byte == 0xaa));
+}
+
+#[test]
+fn test_scrypt_with_memory_too_small() {
+    let params = Params::new(4, 1, 1).unwrap();
+    assert_eq!(params.memory_required(), (16 + 1 + 1) * 128);
+
+    let mut result = [0u8; 32];
+    let mut memory = vec![0u8; params.memory_required() - 1];
+    assert_eq!(
+        scrypt_with_memory(b"password", b"salt", &params, &mut result, &mut memory),
+        Err(errors::Error::InvalidMemoryLen)
+    );
+}
+
+#[test]
+fn test_params_r_p_product_overflow() {
+    // `r * p` wraps to 0 as a `u32`
+    assert_eq!(
+        Params::new(4, 0x1_0000, 0x1_0000),
+        Err(errors::InvalidParams)
+    );
+    assert_eq!(Params::new(4, 0x4000_0000, 4), Err(errors::InvalidParams));
+    assert_eq!(Params::new(4, 0x8000, 0x8000), Err(errors::InvalidParams));
+}
+
+#[cfg(feature = "phc")]
+#[test]
+fn test_scrypt_output_len_mismatch() {
+    let params = Params::new_with_output_len(4, 1, 1, 64).unwrap();
+
+    let mut result = [0u8; 64];
+    scrypt(b"password", b"salt", &params, &mut result).unwrap();
+
+    let mut result = [0u8; 32];
+    assert_eq!(
+        scrypt(b"password", b"salt", &params, &mut result),
+        Err(errors::InvalidOutputLen)
+    );
+
+    let mut memory = vec![0u8; params.memory_required()];
+    assert_eq!(
+        scrypt_with_memory(b"password", b"salt", &params, &mut result, &mut memory),
+        Err(errors::Error::InvalidOutputLen)
+    );
+}
+
+#[test]
+fn test_verifier_limits() {
+    let params = Params::new(10, 8, 2).unwrap();
+    assert_eq!(VerifierLimits::default().check(&params), Ok(()));
+
+    let limits = VerifierLimits {
+        max_memory: params.memory_required(),
+        max_log_n: 10,
+        max_p: 2,
+    };
+    assert_eq!(limits.check(&params), Ok(()));
+
+    for limits in [
+        VerifierLimits {
+            max_memory: params.memory_required() - 1,
+            ..limits
+        },
+        VerifierLimits {
+            max_log_n: 9,
+            ..limits
+        },
+        VerifierLimits { max_p: 1, ..limits },
+    ] {
+        assert_eq!(
+            limits.check(&params),
+            Err(errors::Error::ParamsExceedLimits)
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_params_pick() {
+    use core::time::Duration;
+
+    // Minimum of 1 MiB and 2^15 Salsa20/8 cores
+    assert_eq!(
+        Params::pick(0, Duration::ZERO),
+        Params::new(10, 8, 1).unwrap()
+    );
+
+    let max_memory = 16 * 1024 * 1024;
+    let params = Params::pick(max_memory, Duration::from_millis(100));
+    assert_eq!(params.r(), 8);
+    assert!(params.n() * 8 * 128 <= max_memory as u64);
+    assert!(Params::new(params.log_n(), params.r(), params.p()).is_ok());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_incremental() {
+    for t in tests().iter() {
+        let params = Params::new(t.log_n, t.r, t.p).unwrap();
+        let mut scrypt = IncrementalScrypt::new(t.password.as_bytes(), t.salt.as_bytes(), &params);
+        assert_eq!(scrypt.total_steps(), 2 * params.n() * u64::from(t.p));
+
+        // Steps which don't evenly divide `N`, so that runs span both loops and lanes
+        let mut percent = 0;
+        while !scrypt.run(1000) {
+            assert!(scrypt.percent_complete() >= percent);
+            percent = scrypt.percent_complete();
+        }
+        assert_eq!(scrypt.steps_done(), scrypt.total_steps());
+        assert_eq!(scrypt.percent_complete(), 100);
+
+        let mut result = vec![0u8; t.expected.len()];
+        scrypt.finish(t.password.as_bytes(), &mut result).unwrap();
+        assert_eq!(result, t.expected);
+    }
+
+    // Remaining steps are computed by `finish`
+    let params = Params::new(10, 8, 2).unwrap();
+    let mut expected = [0u8; 32];
+    scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+
+    let mut scrypt = IncrementalScrypt::new(b"password", b"salt", &params);
+    scrypt.run(1500);
+    assert_eq!(scrypt.percent_complete(), 36);
+
+    // The working memory is derived from the password, so it isn't printed
+    let debug = format!("{scrypt:?}");
+    assert!(debug.contains("lane: 0, step: 1500, .."));
+    assert!(!debug.contains("memory"));
+
+    let mut result = [0u8; 32];
+    scrypt.finish(b"password", &mut result).unwrap();
+    assert_eq!(result, expected);
+}